@@ -137,6 +137,7 @@ pub type UntypedStatement = Statement<(), UntypedExpr>;
 pub enum Statement<T, Expr> {
     Fn {
         location: SrcSpan,
+        end_position: usize,
         name: String,
         args: Vec<Arg<T>>,
         body: Expr,
@@ -144,6 +145,7 @@ pub enum Statement<T, Expr> {
         return_annotation: Option<TypeAst>,
         return_type: T,
         doc: Option<String>,
+        deprecation: Option<String>,
     },
 
     TypeAlias {
@@ -154,6 +156,7 @@ pub enum Statement<T, Expr> {
         typ: T,
         public: bool,
         doc: Option<String>,
+        deprecation: Option<String>,
     },
 
     CustomType {
@@ -163,6 +166,7 @@ pub enum Statement<T, Expr> {
         public: bool,
         constructors: Vec<RecordConstructor>,
         doc: Option<String>,
+        deprecation: Option<String>,
     },
 
     ExternalFn {
@@ -175,6 +179,7 @@ pub enum Statement<T, Expr> {
         module: String,
         fun: String,
         doc: Option<String>,
+        deprecation: Option<String>,
     },
 
     ExternalType {
@@ -205,6 +210,17 @@ impl<A, B> Statement<A, B> {
         }
     }
 
+    pub fn deprecation(&self) -> &Option<String> {
+        match self {
+            Statement::Import { .. } | Statement::ExternalType { .. } => &None,
+
+            Statement::Fn { deprecation, .. }
+            | Statement::TypeAlias { deprecation, .. }
+            | Statement::CustomType { deprecation, .. }
+            | Statement::ExternalFn { deprecation, .. } => deprecation,
+        }
+    }
+
     pub fn put_doc<'a>(&mut self, new_doc: impl Iterator<Item = &'a str>) {
         let mut new_doc = new_doc.peekable();
         if new_doc.peek().is_none() {
@@ -261,6 +277,9 @@ pub enum BinOp {
     GtEqFloat,
     GtFloat,
 
+    // Strings
+    Concatenate,
+
     // Maths
     AddInt,
     AddFloat,
@@ -292,9 +311,11 @@ impl BinOp {
             | Self::GtFloat => 4,
 
             // Pipe is 5
-            Self::AddInt | Self::AddFloat | Self::SubInt | Self::SubFloat => 6,
+            Self::Concatenate => 6,
+
+            Self::AddInt | Self::AddFloat | Self::SubInt | Self::SubFloat => 7,
 
-            Self::MultInt | Self::MultFloat | Self::DivInt | Self::DivFloat | Self::ModuloInt => 7,
+            Self::MultInt | Self::MultFloat | Self::DivInt | Self::DivFloat | Self::ModuloInt => 8,
         }
     }
 }
@@ -415,6 +436,40 @@ pub enum ClauseGuard<Type> {
         location: SrcSpan,
         value: String,
     },
+
+    String {
+        location: SrcSpan,
+        value: String,
+    },
+
+    Tuple {
+        location: SrcSpan,
+        elems: Vec<Self>,
+    },
+
+    TupleIndex {
+        location: SrcSpan,
+        index: u64,
+        typ: Type,
+        tuple: Box<Self>,
+    },
+
+    FieldAccess {
+        location: SrcSpan,
+        label: String,
+        // The position of the field in the record, known once type checked
+        index: Option<u64>,
+        typ: Type,
+        container: Box<Self>,
+    },
+
+    Constructor {
+        location: SrcSpan,
+        module: Option<String>,
+        name: String,
+        args: Vec<CallArg<Self>>,
+        typ: Type,
+    },
 }
 
 impl<A> ClauseGuard<A> {
@@ -435,6 +490,11 @@ impl<A> ClauseGuard<A> {
             ClauseGuard::LtEqFloat { location, .. } => location,
             ClauseGuard::Int { location, .. } => location,
             ClauseGuard::Float { location, .. } => location,
+            ClauseGuard::String { location, .. } => location,
+            ClauseGuard::Tuple { location, .. } => location,
+            ClauseGuard::TupleIndex { location, .. } => location,
+            ClauseGuard::FieldAccess { location, .. } => location,
+            ClauseGuard::Constructor { location, .. } => location,
         }
     }
 }
@@ -445,6 +505,11 @@ impl TypedClauseGuard {
             ClauseGuard::Var { typ, .. } => typ.clone(),
             ClauseGuard::Int { .. } => typ::int(),
             ClauseGuard::Float { .. } => typ::float(),
+            ClauseGuard::String { .. } => typ::string(),
+            ClauseGuard::Tuple { elems, .. } => typ::tuple(elems.iter().map(|e| e.typ()).collect()),
+            ClauseGuard::TupleIndex { typ, .. }
+            | ClauseGuard::FieldAccess { typ, .. }
+            | ClauseGuard::Constructor { typ, .. } => typ.clone(),
             _ => typ::bool(),
         }
     }
@@ -69,6 +69,21 @@ pub enum TypedExpr {
         right: Box<Self>,
     },
 
+    NegateInt {
+        location: SrcSpan,
+        value: Box<Self>,
+    },
+
+    NegateFloat {
+        location: SrcSpan,
+        value: Box<Self>,
+    },
+
+    NotBool {
+        location: SrcSpan,
+        value: Box<Self>,
+    },
+
     Pipe {
         location: SrcSpan,
         typ: Arc<Type>,
@@ -81,6 +96,7 @@ pub enum TypedExpr {
         typ: Arc<Type>,
         value: Box<Self>,
         pattern: Pattern<PatternConstructor>,
+        annotation: Option<TypeAst>,
         then: Box<Self>,
         assert: bool,
     },
@@ -124,6 +140,13 @@ pub enum TypedExpr {
 
     Todo {
         location: SrcSpan,
+        message: Option<String>,
+        typ: Arc<Type>,
+    },
+
+    Panic {
+        location: SrcSpan,
+        message: Option<String>,
         typ: Arc<Type>,
     },
 }
@@ -138,12 +161,16 @@ impl TypedExpr {
             Self::ListNil { location, .. } => location,
             Self::Let { then, .. } => then.location(),
             Self::Todo { location, .. } => location,
+            Self::Panic { location, .. } => location,
             Self::Case { location, .. } => location,
             Self::ListCons { location, .. } => location,
             Self::Call { location, .. } => location,
             Self::Pipe { location, .. } => location,
             Self::Float { location, .. } => location,
             Self::BinOp { location, .. } => location,
+            Self::NegateInt { location, .. } => location,
+            Self::NegateFloat { location, .. } => location,
+            Self::NotBool { location, .. } => location,
             Self::Tuple { location, .. } => location,
             Self::String { location, .. } => location,
             Self::TupleIndex { location, .. } => location,
@@ -160,12 +187,16 @@ impl TypedExpr {
             Self::Int { typ, .. } => typ.clone(),
             Self::Seq { then, .. } => then.typ(),
             Self::Todo { typ, .. } => typ.clone(),
+            Self::Panic { typ, .. } => typ.clone(),
             Self::Case { typ, .. } => typ.clone(),
             Self::ListCons { typ, .. } => typ.clone(),
             Self::Call { typ, .. } => typ.clone(),
             Self::Pipe { typ, .. } => typ.clone(),
             Self::Float { typ, .. } => typ.clone(),
             Self::BinOp { typ, .. } => typ.clone(),
+            Self::NegateInt { .. } => typ::int(),
+            Self::NegateFloat { .. } => typ::float(),
+            Self::NotBool { .. } => typ::bool(),
             Self::Tuple { typ, .. } => typ.clone(),
             Self::String { typ, .. } => typ.clone(),
             Self::TupleIndex { typ, .. } => typ.clone(),
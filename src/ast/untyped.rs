@@ -59,6 +59,21 @@ pub enum UntypedExpr {
         right: Box<Self>,
     },
 
+    NegateInt {
+        location: SrcSpan,
+        value: Box<Self>,
+    },
+
+    NegateFloat {
+        location: SrcSpan,
+        value: Box<Self>,
+    },
+
+    NotBool {
+        location: SrcSpan,
+        value: Box<Self>,
+    },
+
     Pipe {
         location: SrcSpan,
         left: Box<Self>,
@@ -69,6 +84,7 @@ pub enum UntypedExpr {
         location: SrcSpan,
         value: Box<Self>,
         pattern: Pattern<()>,
+        annotation: Option<TypeAst>,
         then: Box<Self>,
         assert: bool,
     },
@@ -98,6 +114,12 @@ pub enum UntypedExpr {
 
     Todo {
         location: SrcSpan,
+        message: Option<String>,
+    },
+
+    Panic {
+        location: SrcSpan,
+        message: Option<String>,
     },
 }
 
@@ -111,12 +133,16 @@ impl UntypedExpr {
             Self::ListNil { location, .. } => location,
             Self::Let { then, .. } => then.location(),
             Self::Todo { location, .. } => location,
+            Self::Panic { location, .. } => location,
             Self::Case { location, .. } => location,
             Self::ListCons { location, .. } => location,
             Self::Call { location, .. } => location,
             Self::Pipe { location, .. } => location,
             Self::Float { location, .. } => location,
             Self::BinOp { location, .. } => location,
+            Self::NegateInt { location, .. } => location,
+            Self::NegateFloat { location, .. } => location,
+            Self::NotBool { location, .. } => location,
             Self::String { location, .. } => location,
             Self::Tuple { location, .. } => location,
             Self::TupleIndex { location, .. } => location,
@@ -136,6 +162,8 @@ impl UntypedExpr {
         match self {
             Self::BinOp { name, .. } => name.precedence(),
             Self::Pipe { .. } => 5,
+            // Unary operators bind tighter than any binary operator
+            Self::NegateInt { .. } | Self::NegateFloat { .. } | Self::NotBool { .. } => 9,
             _ => std::u8::MAX,
         }
     }
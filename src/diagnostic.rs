@@ -31,6 +31,61 @@ pub fn write(mut buffer: &mut Buffer, d: Diagnostic, severity: Severity) {
     emit(&mut buffer, &config, &files, &diagnostic).unwrap();
 }
 
+/// A labelled location in one of the files of a `MultiFileDiagnostic`.
+///
+pub struct FileLabel {
+    pub file: String,
+    pub src: String,
+    pub location: crate::ast::SrcSpan,
+    pub label: String,
+}
+
+/// Describes a problem spanning several files (eg. the imports that form an
+/// import cycle). The first label is the primary one.
+///
+pub struct MultiFileDiagnostic {
+    pub title: String,
+    pub labels: Vec<FileLabel>,
+}
+
+pub fn write_multi_file(mut buffer: &mut Buffer, d: MultiFileDiagnostic, severity: Severity) {
+    use codespan::Files;
+    use codespan_reporting::diagnostic::Label;
+    use codespan_reporting::term::emit;
+    use std::collections::HashMap;
+
+    let mut files = Files::new();
+    let mut file_ids = HashMap::new();
+    let mut labels: Vec<_> = d
+        .labels
+        .into_iter()
+        .map(
+            |FileLabel {
+                 file,
+                 src,
+                 location,
+                 label,
+             }| {
+                let file_id = *file_ids
+                    .entry(file.clone())
+                    .or_insert_with(|| files.add(file, src));
+                Label::new(
+                    file_id,
+                    (location.start as u32)..(location.end as u32),
+                    label,
+                )
+            },
+        )
+        .collect();
+
+    let primary = labels.remove(0);
+    let diagnostic = codespan_reporting::diagnostic::Diagnostic::new(severity, d.title, primary)
+        .with_secondary_labels(labels);
+
+    let config = codespan_reporting::term::Config::default();
+    emit(&mut buffer, &config, &files, &diagnostic).unwrap();
+}
+
 pub fn buffer_writer() -> termcolor::BufferWriter {
     // Don't add color codes to the output if standard error isn't connected to a terminal
     let color_choice = if atty::is(atty::Stream::Stderr) {
@@ -49,13 +104,25 @@ pub struct ProjectErrorDiagnostic {
     pub label: String,
 }
 
+/// Describes a problem with the project that does not stop compilation (eg. an
+/// unknown key in gleam.toml).
+///
+pub struct ProjectWarningDiagnostic {
+    pub title: String,
+    pub label: String,
+}
+
 pub fn write_title(buffer: &mut Buffer, title: &str) {
+    write_severity_title(buffer, title, termcolor::Color::Red, "error")
+}
+
+fn write_severity_title(buffer: &mut Buffer, title: &str, color: termcolor::Color, severity: &str) {
     use std::io::Write;
-    use termcolor::{Color, ColorSpec, WriteColor};
+    use termcolor::{ColorSpec, WriteColor};
     buffer
-        .set_color(ColorSpec::new().set_bold(true).set_fg(Some(Color::Red)))
+        .set_color(ColorSpec::new().set_bold(true).set_fg(Some(color)))
         .unwrap();
-    write!(buffer, "error").unwrap();
+    write!(buffer, "{}", severity).unwrap();
     buffer.set_color(ColorSpec::new().set_bold(true)).unwrap();
     write!(buffer, ": {}\n\n", title).unwrap();
     buffer.set_color(&ColorSpec::new()).unwrap();
@@ -68,3 +135,16 @@ pub fn write_project(buffer: &mut Buffer, d: ProjectErrorDiagnostic) {
     buffer.set_color(&ColorSpec::new()).unwrap();
     write!(buffer, "{}", d.label).unwrap();
 }
+
+pub fn write_project_warning(buffer: &mut Buffer, d: ProjectWarningDiagnostic) {
+    use std::io::Write;
+    use termcolor::{ColorSpec, WriteColor};
+    write_severity_title(
+        buffer,
+        d.title.as_ref(),
+        termcolor::Color::Yellow,
+        "warning",
+    );
+    buffer.set_color(&ColorSpec::new()).unwrap();
+    write!(buffer, "{}", d.label).unwrap();
+}
@@ -1,7 +1,9 @@
 use crate::{
     ast::{Statement, TypedStatement},
     error::GleamExpect,
-    format, pretty,
+    format,
+    line_numbers::LineNumbers,
+    pretty,
     project::{Analysed, ModuleOrigin, OutputFile, ProjectConfig},
 };
 use askama::Template;
@@ -10,75 +12,185 @@ use std::path::PathBuf;
 
 const MAX_COLUMNS: isize = 65;
 
+/// The directory within the generated site that the documentation for path
+/// dependencies is written to. The leading underscore means it cannot clash
+/// with the directory of a module.
+const PACKAGES_DIR: &str = "_packages";
+
+/// A path dependency that is to be documented alongside the project.
+///
+pub struct PathDependency {
+    pub config: ProjectConfig,
+    pub root: PathBuf,
+}
+
+struct Package<'a> {
+    config: &'a ProjectConfig,
+    root: PathBuf,
+    // Where the package's pages live relative to the root of the site
+    site_path: Vec<String>,
+    modules: Vec<&'a Analysed>,
+}
+
 pub fn generate_html(
     project_config: &ProjectConfig,
+    path_dependencies: &[PathDependency],
     analysed: &[Analysed],
     files: &mut Vec<OutputFile>,
     dir: &PathBuf,
 ) {
-    let modules = analysed.iter().filter(|m| m.origin == ModuleOrigin::Src);
-
-    let mut modules_links: Vec<_> = modules
-        .clone()
-        .map(|m| {
-            let name = m.name.join("/");
-            let path = name.clone();
-            Link { path, name }
+    let mut packages = vec![Package {
+        config: project_config,
+        root: dir.join(".."),
+        site_path: vec![],
+        modules: analysed
+            .iter()
+            .filter(|m| m.origin == ModuleOrigin::Src)
+            .collect(),
+    }];
+
+    for dependency in path_dependencies {
+        let src = dependency.root.join("src");
+        let src = src.canonicalize().unwrap_or(src);
+        packages.push(Package {
+            config: &dependency.config,
+            root: dependency.root.clone(),
+            site_path: vec![PACKAGES_DIR.to_string(), dependency.config.name.clone()],
+            modules: analysed
+                .iter()
+                .filter(|m| m.origin == ModuleOrigin::Dependency && m.source_base_path == src)
+                .collect(),
+        });
+    }
+
+    // Only list the packages in the sidebar if there is more than one
+    let packages_links: Vec<_> = if packages.len() > 1 {
+        packages
+            .iter()
+            .map(|package| Link {
+                name: package.config.name.clone(),
+                path: site_path(&package.site_path, &[]),
+            })
+            .collect()
+    } else {
+        vec![]
+    };
+
+    for package in packages.iter() {
+        generate_package_html(package, packages_links.as_slice(), files, dir);
+    }
+
+    // Render static assets
+    files.push(OutputFile {
+        path: dir.join("index.css"),
+        text: std::include_str!("../templates/index.css").to_string(),
+    });
+}
+
+fn generate_package_html(
+    package: &Package<'_>,
+    packages: &[Link],
+    files: &mut Vec<OutputFile>,
+    dir: &PathBuf,
+) {
+    let config = package.config;
+    let project_version = config.version.as_deref().unwrap_or("");
+    let project_description = config.description.as_deref().unwrap_or("");
+    let package_dir = package
+        .site_path
+        .iter()
+        .fold(dir.clone(), |path, segment| path.join(segment));
+
+    let mut modules_links: Vec<_> = package
+        .modules
+        .iter()
+        .map(|m| Link {
+            name: m.name.join("/"),
+            path: site_path(&package.site_path, &m.name),
         })
         .collect();
     modules_links.sort();
 
     let pages = &[Link {
         name: "README".to_string(),
-        path: "".to_string(),
+        path: site_path(&package.site_path, &[]),
     }];
-    let links = &[];
+
+    let mut links: Vec<_> = config
+        .links
+        .iter()
+        .map(|link| Link {
+            name: link.title.clone(),
+            path: link.href.clone(),
+        })
+        .collect();
+    if let Some(url) = config.repository.url() {
+        links.push(Link {
+            name: "Repository".to_string(),
+            path: url,
+        });
+    }
 
     // Generate README page
     let readme = PageTemplate {
-        unnest: ".".to_string(),
-        links,
+        unnest: unnest(package.site_path.len()),
+        links: &links,
         pages,
+        packages,
         modules: &modules_links,
-        project_name: &project_config.name,
-        page_title: &project_config.name,
-        project_version: "", // TODO
+        project_name: &config.name,
+        page_title: &config.name,
+        project_version,
+        project_description,
         content: render_markdown(
-            std::fs::read_to_string(dir.join("..").join("README.md"))
+            std::fs::read_to_string(package.root.join("README.md"))
                 .unwrap_or_default()
                 .as_ref(),
         ),
     };
     files.push(OutputFile {
-        path: dir.join("index.html"),
+        path: package_dir.join("index.html"),
         text: readme.render().gleam_expect("README template rendering"),
     });
 
     // Generate module documentation pages
-    for module in modules {
+    for module in package.modules.iter() {
         let name = module.name.join("/");
+        let source = Source::new(config, module);
         let template = ModuleTemplate {
-            unnest: module.name.iter().map(|_| "..").intersperse("/").collect(),
-            links,
+            unnest: unnest(package.site_path.len() + module.name.len()),
+            links: &links,
             pages,
+            packages,
             documentation: render_markdown(module.ast.documentation.iter().join("\n").as_str()),
             modules: modules_links.as_slice(),
-            project_name: &project_config.name,
-            page_title: &format!("{} - {}", name, project_config.name),
+            project_name: &config.name,
+            page_title: &format!("{} - {}", name, config.name),
             module_name: name,
-            project_version: "", // TODO
+            project_version,
+            project_description,
             functions: {
-                let mut f: Vec<_> = module.ast.statements.iter().flat_map(function).collect();
+                let mut f: Vec<_> = module
+                    .ast
+                    .statements
+                    .iter()
+                    .flat_map(|s| function(s, &source))
+                    .collect();
                 f.sort();
                 f
             },
             types: {
-                let mut t: Vec<_> = module.ast.statements.iter().flat_map(type_).collect();
+                let mut t: Vec<_> = module
+                    .ast
+                    .statements
+                    .iter()
+                    .flat_map(|s| type_(s, &source))
+                    .collect();
                 t.sort();
                 t
             },
         };
-        let mut path = dir.clone();
+        let mut path = package_dir.clone();
         for segment in module.name.iter() {
             path.push(segment);
         }
@@ -90,15 +202,67 @@ pub fn generate_html(
                 .gleam_expect("Module documentation template rendering"),
         });
     }
+}
 
-    // Render static assets
-    files.push(OutputFile {
-        path: dir.join("index.css"),
-        text: std::include_str!("../templates/index.css").to_string(),
-    });
+/// The path of a page relative to the root of the site.
+///
+fn site_path(package: &[String], module: &[String]) -> String {
+    package
+        .iter()
+        .chain(module.iter())
+        .map(|segment| format!("{}/", segment))
+        .collect()
+}
+
+/// The relative path from a page nested `depth` directories deep back to the
+/// root of the site.
+///
+fn unnest(depth: usize) -> String {
+    if depth == 0 {
+        ".".to_string()
+    } else {
+        (0..depth).map(|_| "..").intersperse("/").collect()
+    }
+}
+
+/// Builds links from definitions to their location in the package's
+/// repository.
+///
+struct Source<'a> {
+    config: &'a ProjectConfig,
+    path: String,
+    line_numbers: LineNumbers,
+}
+
+impl<'a> Source<'a> {
+    fn new(config: &'a ProjectConfig, module: &Analysed) -> Self {
+        let root = module
+            .source_base_path
+            .parent()
+            .unwrap_or_else(|| module.source_base_path.as_path());
+        let path = module
+            .path
+            .strip_prefix(root)
+            .unwrap_or_else(|_| module.path.as_path())
+            .to_string_lossy()
+            .replace("\\", "/");
+        Self {
+            config,
+            path,
+            line_numbers: LineNumbers::new(&module.src),
+        }
+    }
+
+    fn url(&self, location: &crate::ast::SrcSpan) -> String {
+        let line = self.line_numbers.line_number(location.start);
+        self.config
+            .repository
+            .source_url(&self.config.version, &self.path, line)
+            .unwrap_or_default()
+    }
 }
 
-fn function<'a>(statement: &'a TypedStatement) -> Option<Function<'a>> {
+fn function<'a>(statement: &'a TypedStatement, source: &Source<'_>) -> Option<Function<'a>> {
     let mut formatter = format::Formatter::new();
     match statement {
         Statement::ExternalFn {
@@ -107,11 +271,15 @@ fn function<'a>(statement: &'a TypedStatement) -> Option<Function<'a>> {
             doc,
             retrn,
             args,
+            location,
+            deprecation,
             ..
         } => Some(Function {
             name,
             signature: print(formatter.external_fn_signature(true, name, args, retrn)),
             documentation: markdown_documentation(doc),
+            deprecation: deprecation.clone().unwrap_or_default(),
+            source_url: source.url(location),
         }),
 
         Statement::Fn {
@@ -120,11 +288,15 @@ fn function<'a>(statement: &'a TypedStatement) -> Option<Function<'a>> {
             doc,
             args,
             return_type: ret,
+            location,
+            deprecation,
             ..
         } => Some(Function {
             name,
             documentation: markdown_documentation(doc),
+            deprecation: deprecation.clone().unwrap_or_default(),
             signature: print(formatter.docs_fn_signature(true, name, args, ret.clone())),
+            source_url: source.url(location),
         }),
 
         _ => None,
@@ -145,7 +317,7 @@ fn render_markdown(text: &str) -> String {
     s
 }
 
-fn type_<'a>(statement: &'a TypedStatement) -> Option<Type<'a>> {
+fn type_<'a>(statement: &'a TypedStatement, source: &Source<'_>) -> Option<Type<'a>> {
     let mut formatter = format::Formatter::new();
     match statement {
         Statement::ExternalType {
@@ -153,12 +325,14 @@ fn type_<'a>(statement: &'a TypedStatement) -> Option<Type<'a>> {
             name,
             doc,
             args,
-            ..
+            location,
         } => Some(Type {
             name,
             definition: print(formatter.external_type(true, name.as_str(), args)),
             documentation: markdown_documentation(doc),
+            deprecation: String::new(),
             constructors: vec![],
+            source_url: source.url(location),
         }),
 
         Statement::CustomType {
@@ -168,12 +342,13 @@ fn type_<'a>(statement: &'a TypedStatement) -> Option<Type<'a>> {
             doc,
             constructors: cs,
             location,
-            ..
+            deprecation,
         } => Some(Type {
             name,
             // TODO: Don't use the same printer for docs as for the formatter
             definition: print(formatter.custom_type(true, name, args, cs.as_slice(), location)),
             documentation: markdown_documentation(doc),
+            deprecation: deprecation.clone().unwrap_or_default(),
             constructors: cs
                 .into_iter()
                 .map(|constructor| TypeConstructor {
@@ -181,6 +356,7 @@ fn type_<'a>(statement: &'a TypedStatement) -> Option<Type<'a>> {
                     documentation: markdown_documentation(&constructor.documentation),
                 })
                 .collect(),
+            source_url: source.url(location),
         }),
 
         Statement::TypeAlias {
@@ -189,12 +365,16 @@ fn type_<'a>(statement: &'a TypedStatement) -> Option<Type<'a>> {
             resolved_type: typ,
             doc,
             args,
+            location,
+            deprecation,
             ..
         } => Some(Type {
             name,
             definition: print(formatter.type_alias(true, name, args, typ)),
             documentation: markdown_documentation(doc),
+            deprecation: deprecation.clone().unwrap_or_default(),
             constructors: vec![],
+            source_url: source.url(location),
         }),
 
         _ => None,
@@ -216,6 +396,8 @@ struct Function<'a> {
     name: &'a str,
     signature: String,
     documentation: String,
+    deprecation: String,
+    source_url: String,
 }
 
 #[derive(PartialEq, Eq, PartialOrd, Ord, Debug)]
@@ -229,7 +411,9 @@ struct Type<'a> {
     name: &'a str,
     definition: String,
     documentation: String,
+    deprecation: String,
     constructors: Vec<TypeConstructor>,
+    source_url: String,
 }
 
 #[derive(Template)]
@@ -239,8 +423,10 @@ struct PageTemplate<'a> {
     page_title: &'a str,
     project_name: &'a str,
     project_version: &'a str,
+    project_description: &'a str,
     pages: &'a [Link],
     links: &'a [Link],
+    packages: &'a [Link],
     modules: &'a [Link],
     content: String,
 }
@@ -253,8 +439,10 @@ struct ModuleTemplate<'a> {
     module_name: String,
     project_name: &'a str,
     project_version: &'a str,
+    project_description: &'a str,
     pages: &'a [Link],
     links: &'a [Link],
+    packages: &'a [Link],
     modules: &'a [Link],
     functions: Vec<Function<'a>>,
     types: Vec<Type<'a>>,
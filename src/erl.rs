@@ -4,7 +4,9 @@ mod tests;
 use crate::{
     ast::*,
     error::GleamExpect,
+    line_numbers::LineNumbers,
     pretty::*,
+    project::ProjectConfig,
     typ::{ModuleValueConstructor, PatternConstructor, ValueConstructor, ValueConstructorVariant},
 };
 use heck::{CamelCase, SnakeCase};
@@ -18,16 +20,20 @@ const INDENT: isize = 4;
 #[derive(Debug, Clone)]
 struct Env<'a> {
     module: &'a [String],
+    function: &'a str,
+    line_numbers: &'a LineNumbers,
     current_scope_vars: im::HashMap<String, usize>,
     erl_function_scope_vars: im::HashMap<String, usize>,
 }
 
 impl<'a> Env<'a> {
-    pub fn new(module: &'a [String]) -> Self {
+    pub fn new(module: &'a [String], function: &'a str, line_numbers: &'a LineNumbers) -> Self {
         Self {
             current_scope_vars: Default::default(),
             erl_function_scope_vars: Default::default(),
             module,
+            function,
+            line_numbers,
         }
     }
 
@@ -88,7 +94,82 @@ pub fn record_definition(name: &str, fields: &[&str]) -> String {
     buffer
 }
 
-pub fn module(module: &TypedModule) -> String {
+/// The OTP application resource file for a project, listing the given Erlang
+/// modules.
+///
+pub fn application(config: &ProjectConfig, modules: &[String]) -> String {
+    let atoms = |names: &[String]| {
+        names
+            .iter()
+            .map(|name| atom(name.clone()).format(80))
+            .join(", ")
+    };
+
+    let applications: Vec<_> = ["kernel", "stdlib"]
+        .iter()
+        .map(|name| name.to_string())
+        .chain(config.dependencies.keys().sorted().cloned())
+        .chain(config.erlang.extra_applications.iter().cloned())
+        .unique()
+        .collect();
+
+    let start_module = match &config.erlang.application_start_module {
+        Some(module) => format!(
+            "\n    {{mod, {{{}, []}}}},",
+            atom(module.clone()).format(80)
+        ),
+        None => "".to_string(),
+    };
+
+    let links = config
+        .repository
+        .url()
+        .map(|url| ("Repository", url))
+        .into_iter()
+        .chain(
+            config
+                .links
+                .iter()
+                .map(|link| (link.title.as_str(), link.href.clone())),
+        )
+        .map(|(title, href)| format!("{{{}, {}}}", erl_string(title), erl_string(&href)))
+        .join(", ");
+
+    format!(
+        r#"{{application, {name}, [
+    {{description, {description}}},
+    {{vsn, {version}}},
+    {{modules, [{modules}]}},
+    {{registered, []}},
+    {{applications, [{applications}]}},{start_module}
+    {{env, []}},
+    {{licenses, [{licences}]}},
+    {{links, [{links}]}}
+]}}.
+"#,
+        name = atom(config.name.clone()).format(80),
+        description = erl_string(config.description.as_deref().unwrap_or("")),
+        version = erl_string(config.version.as_deref().unwrap_or("0.0.0")),
+        modules = atoms(modules),
+        applications = atoms(applications.as_slice()),
+        start_module = start_module,
+        licences = config.licences.iter().map(|l| erl_string(l)).join(", "),
+        links = links,
+    )
+}
+
+/// An Erlang string literal, which is a list of characters rather than the
+/// binary that Gleam strings are compiled to.
+///
+fn erl_string(value: &str) -> String {
+    format!("\"{}\"", value.replace("\\", "\\\\").replace("\"", "\\\""))
+}
+
+/// Generate an Erlang module. Each function is preceded by a `-file`
+/// attribute so that Erlang errors and tooling refer to the line of the
+/// Gleam source file at `path` that the function was defined on.
+///
+pub fn module(module: &TypedModule, line_numbers: &LineNumbers, path: &str) -> String {
     let module_name = module.name.as_slice();
     let exports = concat(
         module
@@ -119,7 +200,7 @@ pub fn module(module: &TypedModule) -> String {
         module
             .statements
             .iter()
-            .flat_map(|s| statement(s, &module_name))
+            .flat_map(|s| statement(s, &module_name, line_numbers, path))
             .intersperse(lines(2)),
     );
 
@@ -142,8 +223,13 @@ pub fn module(module: &TypedModule) -> String {
         .format(80)
 }
 
-fn statement(statement: &TypedStatement, module: &[String]) -> Option<Document> {
-    match statement {
+fn statement(
+    statement: &TypedStatement,
+    module: &[String],
+    line_numbers: &LineNumbers,
+    path: &str,
+) -> Option<Document> {
+    let document = match statement {
         Statement::TypeAlias { .. } => None,
         Statement::CustomType { .. } => None,
         Statement::Import { .. } => None,
@@ -151,7 +237,13 @@ fn statement(statement: &TypedStatement, module: &[String]) -> Option<Document>
 
         Statement::Fn {
             args, name, body, ..
-        } => Some(mod_fun(name.as_ref(), args.as_slice(), body, module)),
+        } => Some(mod_fun(
+            name.as_ref(),
+            args.as_slice(),
+            body,
+            module,
+            line_numbers,
+        )),
 
         Statement::ExternalFn {
             fun,
@@ -165,19 +257,59 @@ fn statement(statement: &TypedStatement, module: &[String]) -> Option<Document>
             fun.as_ref(),
             args.len(),
         )),
-    }
+    };
+
+    let start_line = line_numbers.line_number(statement.location().start);
+    document.map(|document| {
+        file_attribute(path, start_line)
+            .append(line())
+            .append(line_number(start_line))
+            .append(document)
+    })
 }
 
-fn mod_fun(name: &str, args: &[TypedArg], body: &TypedExpr, module: &[String]) -> Document {
-    let mut env = Env::new(module);
+fn file_attribute(path: &str, line_number: usize) -> Document {
+    format!("-file({}, {}).", erl_string(path), line_number).to_doc()
+}
+
+fn mod_fun(
+    name: &str,
+    args: &[TypedArg],
+    body: &TypedExpr,
+    module: &[String],
+    line_numbers: &LineNumbers,
+) -> Document {
+    let mut env = Env::new(module, name, line_numbers);
 
     atom(name.to_string())
         .append(fun_args(args, &mut env))
         .append(" ->")
-        .append(line().append(expr(body, &mut env)).nest(INDENT).group())
+        .append(
+            source_line(body, &env)
+                .append(expr(body, &mut env))
+                .nest(INDENT)
+                .group(),
+        )
         .append(".")
 }
 
+/// A line break to the line of the Gleam source on which the expression
+/// starts, so that the line numbers in Erlang errors and stacktraces point to
+/// the Gleam code. Where the Erlang output is already past that line, as
+/// happens when it puts things on separate lines that were on one line in
+/// Gleam, this is a single line break.
+fn source_line(expression: &TypedExpr, env: &Env) -> Document {
+    line_to(env.line_numbers.line_number(expression_start(expression)))
+}
+
+fn expression_start(expression: &TypedExpr) -> usize {
+    match expression {
+        TypedExpr::Seq { first, .. } => expression_start(first),
+        TypedExpr::Let { location, .. } => location.start,
+        _ => expression.location().start,
+    }
+}
+
 fn fun_args(args: &[TypedArg], env: &mut Env) -> Document {
     wrap_args(args.into_iter().map(|a| match &a.names {
         ArgNames::Discard { .. } | ArgNames::LabelledDiscard { .. } => "_".to_doc(),
@@ -227,6 +359,27 @@ fn string(value: &str) -> Document {
     value.to_doc().surround("<<\"", "\"/utf8>>")
 }
 
+fn erlang_error(name: &str, message: &str, location: &SrcSpan, env: &Env) -> Document {
+    let fields = vec![
+        ("gleam_error", atom(name.to_string())),
+        ("message", string(message)),
+        ("module", string(&env.module.join("/"))),
+        ("function", string(env.function)),
+        (
+            "line",
+            env.line_numbers.line_number(location.start).to_doc(),
+        ),
+    ];
+    let fields = fields
+        .into_iter()
+        .map(|(key, value)| key.to_doc().append(" => ").append(value));
+    let error = concat(fields.intersperse(delim(",")))
+        .nest_current()
+        .surround("#{", "}")
+        .group();
+    "erlang:error".to_doc().append(error.surround("(", ")"))
+}
+
 fn tuple(elems: impl Iterator<Item = Document>) -> Document {
     concat(elems.intersperse(delim(",")))
         .nest_current()
@@ -238,7 +391,7 @@ fn seq(first: &TypedExpr, then: &TypedExpr, env: &mut Env) -> Document {
     force_break()
         .append(expr(first, env))
         .append(",")
-        .append(line())
+        .append(source_line(then, env))
         .append(expr(then, env))
 }
 
@@ -262,6 +415,7 @@ fn bin_op(name: &BinOp, left: &TypedExpr, right: &TypedExpr, env: &mut Env) -> D
         BinOp::DivInt => "div",
         BinOp::DivFloat => "/",
         BinOp::ModuloInt => "rem",
+        BinOp::Concatenate => return string_concatenate(left, right, env),
     };
 
     expr(left, env)
@@ -271,6 +425,49 @@ fn bin_op(name: &BinOp, left: &TypedExpr, right: &TypedExpr, env: &mut Env) -> D
         .append(expr(right, env))
 }
 
+/// Chains of string concatenation are flattened into a single binary
+/// construction rather than building a new binary for each `<>`.
+///
+fn string_concatenate(left: &TypedExpr, right: &TypedExpr, env: &mut Env) -> Document {
+    let mut segments = vec![];
+    concatenate_segments(left, &mut segments);
+    concatenate_segments(right, &mut segments);
+    let segments: Vec<_> = segments
+        .into_iter()
+        .map(|segment| binary_segment(segment, env))
+        .collect();
+    concat(segments.into_iter().intersperse(delim(",")))
+        .nest_current()
+        .surround("<<", ">>")
+        .group()
+}
+
+fn concatenate_segments<'a>(value: &'a TypedExpr, segments: &mut Vec<&'a TypedExpr>) {
+    match value {
+        TypedExpr::BinOp {
+            name: BinOp::Concatenate,
+            left,
+            right,
+            ..
+        } => {
+            concatenate_segments(left, segments);
+            concatenate_segments(right, segments);
+        }
+        _ => segments.push(value),
+    }
+}
+
+fn binary_segment(value: &TypedExpr, env: &mut Env) -> Document {
+    match value {
+        TypedExpr::String { value, .. } => value.as_str().to_doc().surround("\"", "\"/utf8"),
+        TypedExpr::Var { .. } => expr(value, env).append("/binary"),
+        _ => "("
+            .to_doc()
+            .append(wrap_expr(value, env))
+            .append(")/binary"),
+    }
+}
+
 fn pipe(value: &TypedExpr, fun: &TypedExpr, env: &mut Env) -> Document {
     let arg = CallArg {
         label: None,
@@ -287,7 +484,7 @@ fn let_(value: &TypedExpr, pat: &TypedPattern, then: &TypedExpr, env: &mut Env)
         .append(" = ")
         .append(body)
         .append(",")
-        .append(line())
+        .append(source_line(then, env))
         .append(expr(then, env))
 }
 
@@ -305,7 +502,7 @@ fn pattern(p: &TypedPattern, env: &mut Env) -> Document {
 
         Pattern::Var { name, .. } => env.next_local_var_name(name.to_string()),
 
-        Pattern::Int { value, .. } => value.as_str().to_doc(),
+        Pattern::Int { value, .. } => int(value.as_str()),
 
         Pattern::Float { value, .. } => float(value.as_ref()),
 
@@ -321,11 +518,31 @@ fn pattern(p: &TypedPattern, env: &mut Env) -> Document {
     }
 }
 
+fn int(value: &str) -> Document {
+    let value = value.replace("_", "");
+    let (sign, value) = match value.get(0..1) {
+        Some("-") => ("-", &value[1..]),
+        _ => ("", value.as_str()),
+    };
+    let radix = match value.get(0..2) {
+        Some("0x") => "16#",
+        Some("0o") => "8#",
+        Some("0b") => "2#",
+        _ => return format!("{}{}", sign, value).to_doc(),
+    };
+    format!("{}{}{}", sign, radix, &value[2..]).to_doc()
+}
+
 fn float(value: &str) -> Document {
-    if value.ends_with(".") {
-        format!("{}0", value).to_doc()
+    let value = value.replace("_", "");
+    let (mantissa, exponent) = match value.find(&['e', 'E'][..]) {
+        Some(i) => value.split_at(i),
+        None => (value.as_str(), ""),
+    };
+    if mantissa.ends_with('.') {
+        format!("{}0{}", mantissa, exponent).to_doc()
     } else {
-        value.to_string().to_doc()
+        value.to_doc()
     }
 }
 
@@ -454,8 +671,12 @@ fn clause(clause: &TypedClause, env: &mut Env) -> Document {
         pattern: pat,
         alternative_patterns,
         then,
-        ..
+        location,
     } = clause;
+    let clause_line = env.line_numbers.line_number(location.start);
+    // A body on the same line as its patterns stays on that line so that the
+    // lines of the clauses that follow are not pushed down
+    let then_line = env.line_numbers.line_number(expression_start(then));
 
     let docs = std::iter::once(pat)
         .chain(alternative_patterns.into_iter())
@@ -471,9 +692,18 @@ fn clause(clause: &TypedClause, env: &mut Env) -> Document {
             patterns_doc
                 .append(optional_clause_guard(guard.as_ref(), env))
                 .append(" ->")
-                .append(line().append(expr(then, env)).nest(INDENT).group())
+                .append(
+                    if then_line == clause_line {
+                        " ".to_doc()
+                    } else {
+                        line_to(then_line)
+                    }
+                    .append(expr(then, env))
+                    .nest(INDENT)
+                    .group(),
+                )
         })
-        .intersperse(";".to_doc().append(lines(2)));
+        .intersperse(";".to_doc().append(line_to(clause_line)));
     concat(docs)
 }
 
@@ -534,16 +764,51 @@ fn bare_clause_guard(guard: &TypedClauseGuard, env: &mut Env) -> Document {
             .append(" =< ")
             .append(clause_guard(right.as_ref(), env)),
 
-        ClauseGuard::Int { value, .. } => value.to_string().to_doc(),
+        ClauseGuard::Int { value, .. } => int(value.as_str()),
+
+        ClauseGuard::Float { value, .. } => float(value.as_str()),
 
-        ClauseGuard::Float { value, .. } => value.to_string().to_doc(),
+        ClauseGuard::String { value, .. } => string(value),
 
         // Only local variables are supported and the typer ensures that all
         // ClauseGuard::Vars are local variables
         ClauseGuard::Var { name, .. } => env.local_var_name(name.to_string()),
+
+        ClauseGuard::Tuple { elems, .. } => tuple(elems.iter().map(|e| clause_guard(e, env))),
+
+        ClauseGuard::TupleIndex { index, tuple, .. } => {
+            clause_guard_element(tuple.as_ref(), index + 1, env)
+        }
+
+        // Records are tagged tuples so their fields start at the second element
+        ClauseGuard::FieldAccess {
+            index, container, ..
+        } => clause_guard_element(
+            container.as_ref(),
+            index.gleam_expect("erl clause guard field access index") + 2,
+            env,
+        ),
+
+        ClauseGuard::Constructor { name, args, .. } if args.is_empty() => {
+            atom(name.to_snake_case())
+        }
+
+        ClauseGuard::Constructor { name, args, .. } => tuple(
+            std::iter::once(atom(name.to_snake_case()))
+                .chain(args.iter().map(|arg| clause_guard(&arg.value, env))),
+        ),
     }
 }
 
+fn clause_guard_element(tuple: &TypedClauseGuard, index: u64, env: &mut Env) -> Document {
+    use std::iter::once;
+    let index_doc = format!("{}", index).to_doc();
+    let tuple_doc = clause_guard(tuple, env);
+    "erlang:element"
+        .to_doc()
+        .append(wrap_args(once(index_doc).chain(once(tuple_doc))))
+}
+
 fn clause_guard(guard: &TypedClauseGuard, env: &mut Env) -> Document {
     match guard {
         // Binary ops are wrapped in parens
@@ -564,23 +829,29 @@ fn clause_guard(guard: &TypedClauseGuard, env: &mut Env) -> Document {
             .append(")"),
 
         // Values are not wrapped
-        ClauseGuard::Var { .. } | ClauseGuard::Int { .. } | ClauseGuard::Float { .. } => {
-            bare_clause_guard(guard, env)
-        }
+        ClauseGuard::Var { .. }
+        | ClauseGuard::Int { .. }
+        | ClauseGuard::Float { .. }
+        | ClauseGuard::String { .. }
+        | ClauseGuard::Tuple { .. }
+        | ClauseGuard::TupleIndex { .. }
+        | ClauseGuard::FieldAccess { .. }
+        | ClauseGuard::Constructor { .. } => bare_clause_guard(guard, env),
     }
 }
 
 fn clauses(cs: &[TypedClause], env: &mut Env) -> Document {
-    concat(
-        cs.into_iter()
-            .map(|c| {
-                let vars = env.current_scope_vars.clone();
-                let erl = clause(c, env);
-                env.current_scope_vars = vars; // Reset the known variables now the clauses' scope has ended
-                erl
-            })
-            .intersperse(";".to_doc().append(lines(2))),
-    )
+    concat(cs.iter().enumerate().map(|(i, c)| {
+        let vars = env.current_scope_vars.clone();
+        let erl = clause(c, env);
+        env.current_scope_vars = vars; // Reset the known variables now the clauses' scope has ended
+        if i == 0 {
+            erl
+        } else {
+            let line = env.line_numbers.line_number(c.location.start);
+            ";".to_doc().append(line_to(line)).append(erl)
+        }
+    }))
 }
 
 fn case(subjects: &[TypedExpr], cs: &[TypedClause], env: &mut Env) -> Document {
@@ -592,11 +863,15 @@ fn case(subjects: &[TypedExpr], cs: &[TypedClause], env: &mut Env) -> Document {
     } else {
         tuple(subjects.into_iter().map(|e| wrap_expr(e, env)))
     };
+    let first_clause_line = match cs.first() {
+        Some(c) => line_to(env.line_numbers.line_number(c.location.start)),
+        None => line(),
+    };
     "case "
         .to_doc()
         .append(subjects_doc)
         .append(" of")
-        .append(line().append(clauses(cs, env)).nest(INDENT))
+        .append(first_clause_line.append(clauses(cs, env)).nest(INDENT))
         .append(line())
         .append("end")
         .group()
@@ -715,8 +990,25 @@ fn wrap_expr(expression: &TypedExpr, env: &mut Env) -> Document {
 fn expr(expression: &TypedExpr, env: &mut Env) -> Document {
     match expression {
         TypedExpr::ListNil { .. } => "[]".to_doc(),
-        TypedExpr::Todo { .. } => "erlang:error({gleam_error, todo})".to_doc(),
-        TypedExpr::Int { value, .. } => value.as_str().to_doc(),
+        TypedExpr::Todo {
+            location, message, ..
+        } => erlang_error(
+            "todo",
+            message
+                .as_deref()
+                .unwrap_or("This has not yet been implemented"),
+            location,
+            env,
+        ),
+        TypedExpr::Panic {
+            location, message, ..
+        } => erlang_error(
+            "panic",
+            message.as_deref().unwrap_or("panic expression evaluated"),
+            location,
+            env,
+        ),
+        TypedExpr::Int { value, .. } => int(value.as_str()),
         TypedExpr::Float { value, .. } => float(value.as_ref()),
         TypedExpr::String { value, .. } => string(value),
         TypedExpr::Seq { first, then, .. } => seq(first, then, env),
@@ -764,10 +1056,38 @@ fn expr(expression: &TypedExpr, env: &mut Env) -> Document {
             name, left, right, ..
         } => bin_op(&name, left, right, env),
 
+        TypedExpr::NegateInt { value, .. } | TypedExpr::NegateFloat { value, .. } => {
+            "-".to_doc().append(unary_operand(value, env))
+        }
+
+        TypedExpr::NotBool { value, .. } => "not ".to_doc().append(unary_operand(value, env)),
+
         TypedExpr::Tuple { elems, .. } => tuple(elems.into_iter().map(|e| wrap_expr(e, env))),
     }
 }
 
+/// Operands of unary operators are wrapped in parens unless they are a single
+/// term, avoiding both precedence problems and `--` being read as the list
+/// subtraction operator.
+///
+fn unary_operand(value: &TypedExpr, env: &mut Env) -> Document {
+    match value {
+        TypedExpr::Int { value: v, .. } | TypedExpr::Float { value: v, .. }
+            if !v.starts_with('-') =>
+        {
+            expr(value, env)
+        }
+
+        TypedExpr::Var { .. }
+        | TypedExpr::Call { .. }
+        | TypedExpr::Tuple { .. }
+        | TypedExpr::TupleIndex { .. }
+        | TypedExpr::RecordAccess { .. } => expr(value, env),
+
+        _ => "(".to_doc().append(wrap_expr(value, env)).append(")"),
+    }
+}
+
 fn tuple_index(tuple: &TypedExpr, index: u64, env: &mut Env) -> Document {
     use std::iter::once;
     let index_doc = format!("{}", (index + 1)).to_doc();
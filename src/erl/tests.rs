@@ -10,6 +10,91 @@ fn record_definition_test() {
     );
 }
 
+#[test]
+fn application_test() {
+    use crate::project::{Dependency, ErlangConfig, Repository};
+
+    let config = ProjectConfig {
+        name: "my_app".to_string(),
+        ..Default::default()
+    };
+    assert_eq!(
+        application(&config, &[]),
+        r#"{application, my_app, [
+    {description, ""},
+    {vsn, "0.0.0"},
+    {modules, []},
+    {registered, []},
+    {applications, [kernel, stdlib]},
+    {env, []},
+    {licenses, []},
+    {links, []}
+]}.
+"#
+    );
+
+    let config = ProjectConfig {
+        name: "my_app".to_string(),
+        version: Some("1.2.0".to_string()),
+        description: Some("It's \"great\"".to_string()),
+        licences: vec!["Apache-2.0".to_string()],
+        repository: Repository::GitHub {
+            user: "lpil".to_string(),
+            repo: "my_app".to_string(),
+        },
+        dependencies: [
+            (
+                "gleam_stdlib".to_string(),
+                Dependency::Hex("~> 0.8".to_string()),
+            ),
+            ("cowboy".to_string(), Dependency::Hex("~> 2.7".to_string())),
+        ]
+        .iter()
+        .cloned()
+        .collect(),
+        dev_dependencies: [(
+            "gleam_test".to_string(),
+            Dependency::Hex("~> 1.0".to_string()),
+        )]
+        .iter()
+        .cloned()
+        .collect(),
+        erlang: ErlangConfig {
+            application_start_module: Some("my_app@app".to_string()),
+            // Applications already included are not repeated
+            extra_applications: vec![
+                "inets".to_string(),
+                "stdlib".to_string(),
+                "cowboy".to_string(),
+                "inets".to_string(),
+            ],
+        },
+        ..Default::default()
+    };
+    assert_eq!(
+        application(
+            &config,
+            &[
+                "my_app".to_string(),
+                "my_app@app".to_string(),
+                "receive".to_string()
+            ]
+        ),
+        r#"{application, my_app, [
+    {description, "It's \"great\""},
+    {vsn, "1.2.0"},
+    {modules, [my_app, my_app@app, 'receive']},
+    {registered, []},
+    {applications, [kernel, stdlib, cowboy, gleam_stdlib, inets]},
+    {mod, {my_app@app, []}},
+    {env, []},
+    {licenses, ["Apache-2.0"]},
+    {links, [{"Repository", "https://github.com/lpil/my_app"}]}
+]}.
+"#
+    );
+}
+
 #[test]
 fn module_test() {
     use std::collections::HashMap;
@@ -32,6 +117,7 @@ fn module_test() {
             },
             Statement::CustomType {
                 doc: None,
+                deprecation: None,
                 location: Default::default(),
                 public: true,
                 name: "Any".to_string(),
@@ -52,6 +138,7 @@ fn module_test() {
             Statement::ExternalFn {
                 return_type: typ::int(),
                 doc: None,
+                deprecation: None,
                 location: Default::default(),
                 args: vec![
                     ExternalFnArg {
@@ -88,6 +175,7 @@ fn module_test() {
             },
             Statement::ExternalFn {
                 doc: None,
+                deprecation: None,
                 location: Default::default(),
                 args: vec![],
                 name: "map".to_string(),
@@ -109,14 +197,19 @@ fn module_test() {
 
 -export([map/0]).
 
+-file(\"src/the_app.gleam\", 1).
 add_ints(A, B) ->
     int:add(A, B).
 
+-file(\"src/the_app.gleam\", 1).
 map() ->
     maps:new().
 "
     .to_string();
-    assert_eq!(expected, module(&m));
+    assert_eq!(
+        expected,
+        module(&m, &LineNumbers::new(""), "src/the_app.gleam")
+    );
 
     let m = Module {
         documentation: vec![],
@@ -130,7 +223,9 @@ map() ->
         name: vec!["term".to_string()],
         statements: vec![
             Statement::Fn {
+                end_position: 0,
                 doc: None,
+                deprecation: None,
                 return_annotation: None,
                 return_type: typ::int(),
                 location: Default::default(),
@@ -144,7 +239,9 @@ map() ->
                 },
             },
             Statement::Fn {
+                end_position: 0,
                 doc: None,
+                deprecation: None,
                 return_annotation: None,
                 return_type: typ::int(),
                 location: Default::default(),
@@ -158,7 +255,9 @@ map() ->
                 },
             },
             Statement::Fn {
+                end_position: 0,
                 doc: None,
+                deprecation: None,
                 return_annotation: None,
                 return_type: typ::int(),
                 location: Default::default(),
@@ -171,7 +270,9 @@ map() ->
                 },
             },
             Statement::Fn {
+                end_position: 0,
                 doc: None,
+                deprecation: None,
                 return_annotation: None,
                 return_type: typ::int(),
                 location: Default::default(),
@@ -185,7 +286,9 @@ map() ->
                 },
             },
             Statement::Fn {
+                end_position: 0,
                 doc: None,
+                deprecation: None,
                 return_annotation: None,
                 return_type: typ::int(),
                 location: Default::default(),
@@ -207,7 +310,9 @@ map() ->
                 },
             },
             Statement::Fn {
+                end_position: 0,
                 doc: None,
+                deprecation: None,
                 return_annotation: None,
                 return_type: typ::int(),
                 location: Default::default(),
@@ -231,7 +336,9 @@ map() ->
                 },
             },
             Statement::Fn {
+                end_position: 0,
                 doc: None,
+                deprecation: None,
                 return_annotation: None,
                 return_type: typ::int(),
                 location: Default::default(),
@@ -244,6 +351,7 @@ map() ->
                         public: true,
                         origin: Default::default(),
                         typ: crate::typ::int(),
+                        deprecation: None,
                         variant: ValueConstructorVariant::Record {
                             name: "Nil".to_string(),
                             field_map: None,
@@ -254,7 +362,9 @@ map() ->
                 },
             },
             Statement::Fn {
+                end_position: 0,
                 doc: None,
+                deprecation: None,
                 return_annotation: None,
                 return_type: typ::int(),
                 location: Default::default(),
@@ -273,12 +383,14 @@ map() ->
                         location: Default::default(),
                         name: "OneTwo".to_string(),
                     },
+                    annotation: None,
                     then: Box::new(TypedExpr::Var {
                         location: Default::default(),
                         constructor: ValueConstructor {
                             public: true,
                             origin: Default::default(),
                             typ: crate::typ::int(),
+                            deprecation: None,
                             variant: ValueConstructorVariant::LocalVariable,
                         },
                         name: "one_two".to_string(),
@@ -287,7 +399,9 @@ map() ->
                 },
             },
             Statement::Fn {
+                end_position: 0,
                 doc: None,
+                deprecation: None,
                 return_annotation: None,
                 return_type: typ::int(),
                 location: Default::default(),
@@ -318,7 +432,9 @@ map() ->
                 },
             },
             Statement::Fn {
+                end_position: 0,
                 doc: None,
+                deprecation: None,
                 return_annotation: None,
                 return_type: typ::int(),
                 location: Default::default(),
@@ -356,7 +472,9 @@ map() ->
                 },
             },
             Statement::Fn {
+                end_position: 0,
                 doc: None,
+                deprecation: None,
                 return_annotation: None,
                 return_type: typ::int(),
                 location: Default::default(),
@@ -385,45 +503,59 @@ map() ->
     let expected = "-module(term).
 -compile(no_auto_import).
 
+-file(\"src/the_app.gleam\", 1).
 int() ->
     176.
 
+-file(\"src/the_app.gleam\", 1).
 float() ->
     11177.324401.
 
+-file(\"src/the_app.gleam\", 1).
 nil() ->
     [].
 
+-file(\"src/the_app.gleam\", 1).
 string() ->
     <<\"Hello there!\"/utf8>>.
 
+-file(\"src/the_app.gleam\", 1).
 seq() ->
     1,
     2.
 
+-file(\"src/the_app.gleam\", 1).
 bin_op() ->
     1 + 2.
 
+-file(\"src/the_app.gleam\", 1).
 enum1() ->
     nil.
 
+-file(\"src/the_app.gleam\", 1).
 let() ->
     OneTwo = 1,
     OneTwo.
 
+-file(\"src/the_app.gleam\", 1).
 conny() ->
     [12, 34].
 
+-file(\"src/the_app.gleam\", 1).
 funny() ->
     fun(OneReallyLongArgToCauseWrapping, AlsoReallyQuiteLong) ->
         100000000000
     end.
 
+-file(\"src/the_app.gleam\", 1).
 tup() ->
     {1, 2.0}.
 "
     .to_string();
-    assert_eq!(expected, module(&m));
+    assert_eq!(
+        expected,
+        module(&m, &LineNumbers::new(""), "src/the_app.gleam")
+    );
 
     let m = Module {
         documentation: vec![],
@@ -436,6 +568,8 @@ tup() ->
         name: vec!["term".to_string()],
         statements: vec![Statement::Fn {
             doc: None,
+            deprecation: None,
+            end_position: 0,
             return_type: typ::int(),
             return_annotation: None,
             location: Default::default(),
@@ -517,6 +651,7 @@ tup() ->
     let expected = "-module(term).
 -compile(no_auto_import).
 
+-file(\"src/the_app.gleam\", 1).
 some_function(
     ArgOne,
     ArgTwo,
@@ -530,7 +665,10 @@ some_function(
     1.
 "
     .to_string();
-    assert_eq!(expected, module(&m));
+    assert_eq!(
+        expected,
+        module(&m, &LineNumbers::new(""), "src/the_app.gleam")
+    );
 
     let m = Module {
         documentation: vec![],
@@ -543,7 +681,9 @@ some_function(
         name: vec!["vars".to_string()],
         statements: vec![
             Statement::Fn {
+                end_position: 0,
                 doc: None,
+                deprecation: None,
                 return_annotation: None,
                 return_type: typ::int(),
                 location: Default::default(),
@@ -557,12 +697,15 @@ some_function(
                         public: true,
                         origin: Default::default(),
                         typ: crate::typ::int(),
+                        deprecation: None,
                         variant: ValueConstructorVariant::LocalVariable,
                     },
                 },
             },
             Statement::Fn {
+                end_position: 0,
                 doc: None,
+                deprecation: None,
                 return_annotation: None,
                 return_type: typ::int(),
                 location: Default::default(),
@@ -579,7 +722,9 @@ some_function(
                 },
             },
             Statement::Fn {
+                end_position: 0,
                 doc: None,
+                deprecation: None,
                 return_annotation: None,
                 return_type: typ::int(),
                 location: Default::default(),
@@ -599,7 +744,9 @@ some_function(
                 },
             },
             Statement::Fn {
+                end_position: 0,
                 doc: None,
+                deprecation: None,
                 return_annotation: None,
                 return_type: typ::int(),
                 location: Default::default(),
@@ -633,20 +780,27 @@ some_function(
     let expected = "-module(vars).
 -compile(no_auto_import).
 
+-file(\"src/the_app.gleam\", 1).
 arg() ->
     SomeArg.
 
+-file(\"src/the_app.gleam\", 1).
 moddy() ->
     fun one:two/0.
 
+-file(\"src/the_app.gleam\", 1).
 moddy2() ->
     fun one@zero:two/2.
 
+-file(\"src/the_app.gleam\", 1).
 moddy4() ->
     one@zero:two(1).
 "
     .to_string();
-    assert_eq!(expected, module(&m));
+    assert_eq!(
+        expected,
+        module(&m, &LineNumbers::new(""), "src/the_app.gleam")
+    );
 
     let m = Module {
         documentation: vec![],
@@ -659,6 +813,8 @@ moddy4() ->
         name: vec!["my_mod".to_string()],
         statements: vec![Statement::Fn {
             doc: None,
+            deprecation: None,
+            end_position: 0,
             return_type: typ::int(),
             return_annotation: None,
             location: Default::default(),
@@ -786,29 +942,22 @@ moddy4() ->
     let expected = "-module(my_mod).
 -compile(no_auto_import).
 
+-file(\"src/the_app.gleam\", 1).
 go() ->
     case 1 of
-        1 ->
-            1;
-
-        1.0 ->
-            1;
-
-        <<\"hello\"/utf8>> ->
-            1;
-
-        [] ->
-            1;
-
-        {error, 2} ->
-            1;
-
-        {1, 2} ->
-            1
+        1 -> 1;
+        1.0 -> 1;
+        <<\"hello\"/utf8>> -> 1;
+        [] -> 1;
+        {error, 2} -> 1;
+        {1, 2} -> 1
     end.
 "
     .to_string();
-    assert_eq!(expected, module(&m));
+    assert_eq!(
+        expected,
+        module(&m, &LineNumbers::new(""), "src/the_app.gleam")
+    );
 
     let m = Module {
         documentation: vec![],
@@ -821,7 +970,9 @@ go() ->
         name: vec!["funny".to_string()],
         statements: vec![
             Statement::Fn {
+                end_position: 0,
                 doc: None,
+                deprecation: None,
                 return_type: typ::int(),
                 return_annotation: None,
                 location: Default::default(),
@@ -852,13 +1003,16 @@ go() ->
                                 arity: 1,
                             },
                             typ: crate::typ::int(),
+                            deprecation: None,
                         },
                         name: "one_two".to_string(),
                     }),
                 },
             },
             Statement::Fn {
+                end_position: 0,
                 doc: None,
+                deprecation: None,
                 return_type: typ::int(),
                 return_annotation: None,
                 location: Default::default(),
@@ -884,13 +1038,16 @@ go() ->
                             origin: Default::default(),
                             variant: ValueConstructorVariant::LocalVariable,
                             typ: crate::typ::int(),
+                            deprecation: None,
                         },
                         name: "one_two".to_string(),
                     }),
                 },
             },
             Statement::Fn {
+                end_position: 0,
                 doc: None,
+                deprecation: None,
                 return_type: typ::int(),
                 return_annotation: None,
                 location: Default::default(),
@@ -927,6 +1084,7 @@ go() ->
                                 public: true,
                                 origin: Default::default(),
                                 typ: crate::typ::int(),
+                                deprecation: None,
                                 variant: ValueConstructorVariant::ModuleFn {
                                     name: "one_two_actual".to_string(),
                                     module: vec!["funny".to_string()],
@@ -944,17 +1102,23 @@ go() ->
     let expected = "-module(funny).
 -compile(no_auto_import).
 
+-file(\"src/the_app.gleam\", 1).
 one() ->
     one_two(1).
 
+-file(\"src/the_app.gleam\", 1).
 two() ->
     OneTwo(1).
 
+-file(\"src/the_app.gleam\", 1).
 three() ->
     (one_two_actual(1))(2).
 "
     .to_string();
-    assert_eq!(expected, module(&m));
+    assert_eq!(
+        expected,
+        module(&m, &LineNumbers::new(""), "src/the_app.gleam")
+    );
 }
 
 #[test]
@@ -967,7 +1131,7 @@ fn integration_test() {
             ast.name = vec!["the_app".to_string()];
             let (result, _) = crate::typ::infer_module(ast, &std::collections::HashMap::new());
             let ast = result.expect("should successfully infer");
-            let output = module(&ast);
+            let output = module(&ast, &LineNumbers::new($src), "src/the_app.gleam");
             assert_eq!(($src, output), ($src, $erl.to_string()));
         };
     }
@@ -980,6 +1144,7 @@ let x = tuple(100000000000000000, tuple(2000000000, 3000000000000, 40000000000),
         r#"-module(the_app).
 -compile(no_auto_import).
 
+-file("src/the_app.gleam", 1).
 go() ->
     X = {100000000000000000,
          {2000000000, 3000000000000, 40000000000},
@@ -998,6 +1163,7 @@ go() ->
         r#"-module(the_app).
 -compile(no_auto_import).
 
+-file("src/the_app.gleam", 1).
 go() ->
     Y = 1,
     Y1 = 2,
@@ -1014,6 +1180,7 @@ go() ->
         r#"-module(the_app).
 -compile(no_auto_import).
 
+-file("src/the_app.gleam", 1).
 go() ->
     Y = 1,
     Y1 = 2,
@@ -1028,6 +1195,7 @@ go() ->
 
 -export([t/0]).
 
+-file("src/the_app.gleam", 1).
 t() ->
     true.
 "#,
@@ -1039,6 +1207,7 @@ t() ->
         r#"-module(the_app).
 -compile(no_auto_import).
 
+-file("src/the_app.gleam", 2).
 pound(X) ->
     {pound, X}.
 "#,
@@ -1049,6 +1218,7 @@ pound(X) ->
         r#"-module(the_app).
 -compile(no_auto_import).
 
+-file("src/the_app.gleam", 1).
 loop() ->
     loop().
 "#,
@@ -1059,6 +1229,7 @@ loop() ->
         r#"-module(the_app).
 -compile(no_auto_import).
 
+-file("src/the_app.gleam", 1).
 run() ->
     'Elixir.MyApp':run().
 "#,
@@ -1072,9 +1243,11 @@ run() ->
 
 -export([go/0]).
 
+-file("src/the_app.gleam", 1).
 inc(X) ->
     X + 1.
 
+-file("src/the_app.gleam", 2).
 go() ->
     inc(inc(inc(1))).
 "#,
@@ -1088,9 +1261,11 @@ go() ->
 
 -export([go/0]).
 
+-file("src/the_app.gleam", 1).
 add(X, Y) ->
     X + Y.
 
+-file("src/the_app.gleam", 2).
 go() ->
     add(add(2, add(1, 1)), 3).
 "#,
@@ -1104,12 +1279,15 @@ go() ->
         r#"-module(the_app).
 -compile(no_auto_import).
 
+-file("src/the_app.gleam", 1).
 'and'(X, Y) ->
     X andalso Y.
 
+-file("src/the_app.gleam", 2).
 'or'(X, Y) ->
     X orelse Y.
 
+-file("src/the_app.gleam", 3).
 modulo(X, Y) ->
     X rem Y.
 "#,
@@ -1122,22 +1300,18 @@ modulo(X, Y) ->
         r#"-module(the_app).
 -compile(no_auto_import).
 
+-file("src/the_app.gleam", 1).
 second(List) ->
     case List of
-        [X, Y] ->
-            Y;
-
-        Z ->
-            1
+        [X, Y] -> Y;
+        Z -> 1
     end.
 
+-file("src/the_app.gleam", 2).
 tail(List) ->
     case List of
-        [X | Xs] ->
-            Xs;
-
-        Z ->
-            List
+        [X | Xs] -> Xs;
+        Z -> List
     end.
 "#,
     );
@@ -1147,10 +1321,10 @@ tail(List) ->
         r#"-module(the_app).
 -compile(no_auto_import).
 
+-file("src/the_app.gleam", 1).
 tail(List) ->
     case List of
-        [X | _] ->
-            X
+        [X | _] -> X
     end.
 "#,
     );
@@ -1163,22 +1337,18 @@ tail(List) ->
         r#"-module(the_app).
 -compile(no_auto_import).
 
+-file("src/the_app.gleam", 1).
 second(List) ->
     case List of
-        [X, Y] ->
-            Y;
-
-        Z ->
-            1
+        [X, Y] -> Y;
+        Z -> 1
     end.
 
+-file("src/the_app.gleam", 2).
 tail(List) ->
     case List of
-        [X | Xs] ->
-            Xs;
-
-        Z ->
-            List
+        [X | Xs] -> Xs;
+        Z -> List
     end.
 "#,
     );
@@ -1188,6 +1358,7 @@ tail(List) ->
         r#"-module(the_app).
 -compile(no_auto_import).
 
+-file("src/the_app.gleam", 1).
 x() ->
     X = 1,
     X1 = X + 1,
@@ -1203,9 +1374,11 @@ x() ->
 
 -export(['receive'/0, 'catch'/1]).
 
+-file("src/the_app.gleam", 1).
 'receive'() ->
     'try':'and'().
 
+-file("src/the_app.gleam", 2).
 'catch'(X) ->
     'receive'().
 "#,
@@ -1217,6 +1390,7 @@ x() ->
         r#"-module(the_app).
 -compile(no_auto_import).
 
+-file("src/the_app.gleam", 1).
 x() ->
     1.0 < 2.3.
 "#,
@@ -1228,6 +1402,7 @@ x() ->
         r#"-module(the_app).
 -compile(no_auto_import).
 
+-file("src/the_app.gleam", 1).
 x() ->
     {pair, 1, 2},
     {pair, 3.0, 4.0}.
@@ -1239,6 +1414,7 @@ x() ->
         r#"-module(the_app).
 -compile(no_auto_import).
 
+-file("src/the_app.gleam", 1).
 x() ->
     null.
 "#,
@@ -1250,6 +1426,7 @@ x() ->
         r#"-module(the_app).
 -compile(no_auto_import).
 
+-file("src/the_app.gleam", 2).
 y() ->
     ((fun() -> fun(A, B) -> {point, A, B} end end)())(4, 6).
 "#,
@@ -1261,6 +1438,7 @@ y() ->
         r#"-module(the_app).
 -compile(no_auto_import).
 
+-file("src/the_app.gleam", 2).
 x() ->
     {point, 4, 6},
     {point, 9, 1}.
@@ -1272,6 +1450,7 @@ x() ->
         r#"-module(the_app).
 -compile(no_auto_import).
 
+-file("src/the_app.gleam", 1).
 x(Y) ->
     {point, A, B} = Y,
     A.
@@ -1284,9 +1463,11 @@ x(Y) ->
         r#"-module(the_app).
 -compile(no_auto_import).
 
+-file("src/the_app.gleam", 1).
 go(A, B) ->
     m:f(A, B).
 
+-file("src/the_app.gleam", 2).
 x() ->
     go(1, 2),
     go(4, 3).
@@ -1299,9 +1480,11 @@ x() ->
         r#"-module(the_app).
 -compile(no_auto_import).
 
+-file("src/the_app.gleam", 1).
 go(Xx, Yy) ->
     Xx.
 
+-file("src/the_app.gleam", 2).
 x() ->
     go(1, 2),
     go(4, 3).
@@ -1317,6 +1500,7 @@ fn create_user(user_id) { User(age: 22, id: user_id, name: "") }
         r#"-module(the_app).
 -compile(no_auto_import).
 
+-file("src/the_app.gleam", 3).
 create_user(UserId) ->
     {user, UserId, <<""/utf8>>, 22}.
 "#,
@@ -1327,10 +1511,10 @@ create_user(UserId) ->
         r#"-module(the_app).
 -compile(no_auto_import).
 
+-file("src/the_app.gleam", 1).
 run() ->
     case {1, 2} of
-        {A, B} ->
-            A
+        {A, B} -> A
     end.
 "#,
     );
@@ -1341,6 +1525,7 @@ run() ->
         r#"-module(the_app).
 -compile(no_auto_import).
 
+-file("src/the_app.gleam", 2).
 x() ->
     {x, 1, 2.0},
     {x, 4, 3.0}.
@@ -1364,14 +1549,14 @@ fn go(a) {
         r#"-module(the_app).
 -compile(no_auto_import).
 
+-file("src/the_app.gleam", 2).
 go(A) ->
     case A of
         99 ->
             A1 = A,
             1;
 
-        _ ->
-            A
+        _ -> A
     end.
 "#,
     );
@@ -1387,6 +1572,7 @@ fn go(a) {
         r#"-module(the_app).
 -compile(no_auto_import).
 
+-file("src/the_app.gleam", 2).
 go(A) ->
     A1 = A + 1,
     A1.
@@ -1404,6 +1590,7 @@ fn go(a) {
         r#"-module(the_app).
 -compile(no_auto_import).
 
+-file("src/the_app.gleam", 2).
 go(A) ->
     A1 = 1,
     A1.
@@ -1423,9 +1610,11 @@ fn main() {
         r#"-module(the_app).
 -compile(no_auto_import).
 
+-file("src/the_app.gleam", 2).
 id(X) ->
     X.
 
+-file("src/the_app.gleam", 6).
 main() ->
     id(fun id/1).
 "#,
@@ -1451,9 +1640,11 @@ pub fn main() {
 
 -export([factory/2, main/0]).
 
+-file("src/the_app.gleam", 2).
 factory(F, I) ->
     F(I).
 
+-file("src/the_app.gleam", 10).
 main() ->
     factory(fun(A) -> {box, A} end, 0).
 "#,
@@ -1478,12 +1669,14 @@ pub fn main(args) {
 
 -export([main/1]).
 
+-file("src/the_app.gleam", 2).
 main(Args) ->
     case Args of
         _ ->
             A = 1,
             A
     end,
+
     A1 = 2,
     A1.
 "#,
@@ -1504,13 +1697,11 @@ pub fn main(args) {
 
 -export([main/1]).
 
+-file("src/the_app.gleam", 2).
 main(Args) ->
     case Args of
-        X when X =:= Args ->
-            1;
-
-        _ ->
-            0
+        X when X =:= Args -> 1;
+        _ -> 0
     end.
 "#,
     );
@@ -1529,13 +1720,11 @@ pub fn main(args) {
 
 -export([main/1]).
 
+-file("src/the_app.gleam", 2).
 main(Args) ->
     case Args of
-        X when (X =/= X) =:= (Args =:= Args) ->
-            1;
-
-        _ ->
-            0
+        X when (X =/= X) =:= (Args =:= Args) -> 1;
+        _ -> 0
     end.
 "#,
     );
@@ -1554,13 +1743,11 @@ pub fn main(args) {
 
 -export([main/1]).
 
+-file("src/the_app.gleam", 2).
 main(Args) ->
     case Args of
-        X when (X andalso X) orelse ((X =:= X) andalso X) ->
-            1;
-
-        _ ->
-            0
+        X when (X andalso X) orelse ((X =:= X) andalso X) -> 1;
+        _ -> 0
     end.
 "#,
     );
@@ -1579,13 +1766,11 @@ pub fn main() {
 
 -export([main/0]).
 
+-file("src/the_app.gleam", 2).
 main() ->
     case {1, 0} of
-        {X, Y} when X > Y ->
-            1;
-
-        {_, _} ->
-            0
+        {X, Y} when X > Y -> 1;
+        {_, _} -> 0
     end.
 "#,
     );
@@ -1604,13 +1789,11 @@ pub fn main() {
 
 -export([main/0]).
 
+-file("src/the_app.gleam", 2).
 main() ->
     case {1, 0} of
-        {X, Y} when X >= Y ->
-            1;
-
-        {_, _} ->
-            0
+        {X, Y} when X >= Y -> 1;
+        {_, _} -> 0
     end.
 "#,
     );
@@ -1629,13 +1812,11 @@ pub fn main() {
 
 -export([main/0]).
 
+-file("src/the_app.gleam", 2).
 main() ->
     case {1, 0} of
-        {X, Y} when X < Y ->
-            1;
-
-        {_, _} ->
-            0
+        {X, Y} when X < Y -> 1;
+        {_, _} -> 0
     end.
 "#,
     );
@@ -1654,13 +1835,11 @@ pub fn main() {
 
 -export([main/0]).
 
+-file("src/the_app.gleam", 2).
 main() ->
     case {1, 0} of
-        {X, Y} when X =< Y ->
-            1;
-
-        {_, _} ->
-            0
+        {X, Y} when X =< Y -> 1;
+        {_, _} -> 0
     end.
 "#,
     );
@@ -1679,13 +1858,11 @@ pub fn main() {
 
 -export([main/0]).
 
+-file("src/the_app.gleam", 2).
 main() ->
     case {1.0, 0.1} of
-        {X, Y} when X > Y ->
-            1;
-
-        {_, _} ->
-            0
+        {X, Y} when X > Y -> 1;
+        {_, _} -> 0
     end.
 "#,
     );
@@ -1704,13 +1881,11 @@ pub fn main() {
 
 -export([main/0]).
 
+-file("src/the_app.gleam", 2).
 main() ->
     case {1.0, 0.1} of
-        {X, Y} when X >= Y ->
-            1;
-
-        {_, _} ->
-            0
+        {X, Y} when X >= Y -> 1;
+        {_, _} -> 0
     end.
 "#,
     );
@@ -1730,14 +1905,12 @@ pub fn main() {
 
 -export([main/0]).
 
+-file("src/the_app.gleam", 2).
 main() ->
     X = 0.123,
     case X of
-        99.9854 ->
-            1;
-
-        _ ->
-            0
+        99.9854 -> 1;
+        _ -> 0
     end.
 "#,
     );
@@ -1756,11 +1929,11 @@ pub fn main() {
 
 -export([main/0]).
 
+-file("src/the_app.gleam", 2).
 main() ->
     X = 0.123,
     case X of
-        _ when X =:= 3.14 ->
-            1
+        _ when X =:= 3.14 -> 1
     end.
 "#,
     );
@@ -1779,11 +1952,113 @@ pub fn main() {
 
 -export([main/0]).
 
+-file("src/the_app.gleam", 2).
 main() ->
     X = 0.123,
     case X of
-        _ when 0.123 < X ->
-            1
+        _ when 0.123 < X -> 1
+    end.
+"#,
+    );
+
+    assert_erl!(
+        r#"
+pub fn greet(name, title) {
+  let greeting = "Hello, " <> title(name) <> "!"
+  greeting <> { "\n" <> name }
+}
+"#,
+        r#"-module(the_app).
+-compile(no_auto_import).
+
+-export([greet/2]).
+
+-file("src/the_app.gleam", 2).
+greet(Name, Title) ->
+    Greeting = <<"Hello, "/utf8, (Title(Name))/binary, "!"/utf8>>,
+    <<Greeting/binary, "\n"/utf8, Name/binary>>.
+"#,
+    );
+
+    assert_erl!(
+        r#"
+pub fn main(x) {
+  case x {
+    0xFF | 0o17 | 0b1010 -> 1_000_000
+    -0x1F -> 1
+    _ if x > 0xff -> 2
+    _ -> 3
+  }
+  [1.5e-3, 2.E10, 1_000.000_1]
+}
+"#,
+        r#"-module(the_app).
+-compile(no_auto_import).
+
+-export([main/1]).
+
+-file("src/the_app.gleam", 2).
+main(X) ->
+    case X of
+        16#FF -> 1000000;
+        8#17 -> 1000000;
+        2#1010 -> 1000000;
+        -16#1F -> 1;
+        _ when X > 16#ff -> 2;
+        _ -> 3
+    end,
+    [1.5e-3, 2.0E10, 1000.0001].
+"#,
+    );
+
+    assert_erl!(
+        r#"
+pub fn main(x, y, z) {
+  let a = -x
+  let b = -.{y +. 1.0}
+  let c = -{-1}
+  !z && !{x > 1}
+}
+"#,
+        r#"-module(the_app).
+-compile(no_auto_import).
+
+-export([main/3]).
+
+-file("src/the_app.gleam", 2).
+main(X, Y, Z) ->
+    A = -X,
+    B = -(Y + 1.0),
+    C = -(-1),
+    not Z andalso not (X > 1).
+"#,
+    );
+
+    assert_erl!(
+        r#"
+pub type Person {
+  Person(name: String, age: Int)
+}
+
+pub fn main(x) {
+  case x {
+    _ if x == Person(age: 30, name: "Bob") -> 1
+    _ if x.name != "Alice" -> 2
+    _ if tuple(x, Nil).0 == x -> 3
+  }
+}
+"#,
+        r#"-module(the_app).
+-compile(no_auto_import).
+
+-export([main/1]).
+
+-file("src/the_app.gleam", 6).
+main(X) ->
+    case X of
+        _ when X =:= {person, <<"Bob"/utf8>>, 30} -> 1;
+        _ when erlang:element(2, X) =/= <<"Alice"/utf8>> -> 2;
+        _ when erlang:element(1, {X, nil}) =:= X -> 3
     end.
 "#,
     );
@@ -1803,14 +2078,12 @@ pub fn main() {
 
 -export([main/0]).
 
+-file("src/the_app.gleam", 2).
 main() ->
     X = 0,
     case X of
-        0 ->
-            1;
-
-        _ ->
-            0
+        0 -> 1;
+        _ -> 0
     end.
 "#,
     );
@@ -1829,11 +2102,11 @@ pub fn main() {
 
 -export([main/0]).
 
+-file("src/the_app.gleam", 2).
 main() ->
     X = 0,
     case X of
-        _ when X =:= 0 ->
-            1
+        _ when X =:= 0 -> 1
     end.
 "#,
     );
@@ -1852,11 +2125,11 @@ pub fn main() {
 
 -export([main/0]).
 
+-file("src/the_app.gleam", 2).
 main() ->
     X = 0,
     case X of
-        _ when 0 < X ->
-            1
+        _ when 0 < X -> 1
     end.
 "#,
     );
@@ -1875,13 +2148,11 @@ pub fn main() {
 
 -export([main/0]).
 
+-file("src/the_app.gleam", 2).
 main() ->
     case {0.1, 1.0} of
-        {X, Y} when X < Y ->
-            1;
-
-        {_, _} ->
-            0
+        {X, Y} when X < Y -> 1;
+        {_, _} -> 0
     end.
 "#,
     );
@@ -1900,13 +2171,11 @@ pub fn main() {
 
 -export([main/0]).
 
+-file("src/the_app.gleam", 2).
 main() ->
     case {0.1, 1.0} of
-        {X, Y} when X =< Y ->
-            1;
-
-        {_, _} ->
-            0
+        {X, Y} when X =< Y -> 1;
+        {_, _} -> 0
     end.
 "#,
     );
@@ -1925,16 +2194,12 @@ pub fn main(args) {
 
 -export([main/1]).
 
+-file("src/the_app.gleam", 2).
 main(Args) ->
     case Args of
-        [X] when X ->
-            1;
-
-        [X1, _] when X1 ->
-            1;
-
-        _ ->
-            0
+        [X] when X -> 1;
+        [X1, _] when X1 -> 1;
+        _ -> 0
     end.
 "#,
     );
@@ -1950,8 +2215,52 @@ pub fn main() {
 
 -export([main/0]).
 
+-file("src/the_app.gleam", 2).
 main() ->
-    erlang:error({gleam_error, todo}).
+    erlang:error(#{gleam_error => todo,
+                   message => <<"This has not yet been implemented"/utf8>>,
+                   module => <<"the_app"/utf8>>,
+                   function => <<"main"/utf8>>,
+                   line => 3}).
+"#,
+    );
+
+    assert_erl!(
+        r#"
+pub fn main() {
+  panic("oh no")
+}
+
+fn go(x) {
+  case x {
+    True -> 1
+    False -> todo("later")
+  }
+}
+"#,
+        r#"-module(the_app).
+-compile(no_auto_import).
+
+-export([main/0]).
+
+-file("src/the_app.gleam", 2).
+main() ->
+    erlang:error(#{gleam_error => panic,
+                   message => <<"oh no"/utf8>>,
+                   module => <<"the_app"/utf8>>,
+                   function => <<"main"/utf8>>,
+                   line => 3}).
+
+-file("src/the_app.gleam", 6).
+go(X) ->
+    case X of
+        true -> 1;
+        false -> erlang:error(#{gleam_error => todo,
+                                message => <<"later"/utf8>>,
+                                module => <<"the_app"/utf8>>,
+                                function => <<"go"/utf8>>,
+                                line => 9})
+    end.
 "#,
     );
 
@@ -1967,9 +2276,11 @@ pub fn get_name(person: Person) { person.name }
 
 -export([get_age/1, get_name/1]).
 
+-file("src/the_app.gleam", 3).
 get_age(Person) ->
     erlang:element(3, Person).
 
+-file("src/the_app.gleam", 4).
 get_name(Person) ->
     erlang:element(2, Person).
 "#,
@@ -1991,6 +2302,7 @@ fn main() {
         r#"-module(the_app).
 -compile(no_auto_import).
 
+-file("src/the_app.gleam", 6).
 main() ->
     Triple = {triple, 1, 2, 3},
     {triple, TheA, _, _} = Triple,
@@ -2014,6 +2326,7 @@ fn main() {
         r#"-module(the_app).
 -compile(no_auto_import).
 
+-file("src/the_app.gleam", 6).
 main() ->
     Triple = {triple, 1, 2, 3},
     {triple, _, TheB, _} = Triple,
@@ -2037,6 +2350,7 @@ fn main() {
         r#"-module(the_app).
 -compile(no_auto_import).
 
+-file("src/the_app.gleam", 6).
 main() ->
     Triple = {triple, 1, 2, 3},
     {triple, TheA, _, TheC} = Triple,
@@ -2061,11 +2375,11 @@ fn main() {
         r#"-module(the_app).
 -compile(no_auto_import).
 
+-file("src/the_app.gleam", 6).
 main() ->
     Triple = {triple, 1, 2, 3},
     case Triple of
-        {triple, _, TheB, _} ->
-            TheB
+        {triple, _, TheB, _} -> TheB
     end.
 "#,
     );
@@ -2080,6 +2394,7 @@ pub fn apply(f: fn(a) -> b, a: a) { a |> f }
 
 -export([apply/2]).
 
+-file("src/the_app.gleam", 2).
 apply(F, A) ->
     F(A).
 "#,
@@ -2095,8 +2410,43 @@ pub fn apply(f: fn(a, Int) -> b, a: a) { a |> f(1) }
 
 -export([apply/2]).
 
+-file("src/the_app.gleam", 2).
 apply(F, A) ->
     F(A, 1).
+"#,
+    );
+
+    // Expressions and clauses in a function body are on the same lines as in
+    // the Gleam source
+    assert_erl!(
+        r#"
+pub fn go(x) {
+  let y = x
+
+  case y {
+    1 -> 2
+    _ -> {
+      let z = y
+      z
+    }
+  }
+}
+"#,
+        r#"-module(the_app).
+-compile(no_auto_import).
+
+-export([go/1]).
+
+-file("src/the_app.gleam", 2).
+go(X) ->
+    Y = X,
+
+    case Y of
+        1 -> 2;
+        _ ->
+            Z = Y,
+            Z
+    end.
 "#,
     );
 }
@@ -0,0 +1,105 @@
+use crate::{
+    error::{Error, FileIOAction, FileKind},
+    project::{Analysed, BuildProfile},
+};
+use std::path::PathBuf;
+use std::process::Command;
+
+#[derive(Debug, PartialEq)]
+pub enum Outcome {
+    Compiled,
+    ErlcNotFound,
+}
+
+/// Compile the Erlang generated for the given modules, and any hand-written
+/// Erlang files, into BEAM files in the output directory. Compilation is
+/// skipped if `erlc` is not installed.
+///
+pub fn compile(
+    modules: &[&Analysed],
+    erlang_files: &[PathBuf],
+    profile: &BuildProfile,
+    out_dir: &PathBuf,
+) -> Result<Outcome, Error> {
+    if modules.is_empty() && erlang_files.is_empty() {
+        return Ok(Outcome::Compiled);
+    }
+
+    std::fs::create_dir_all(out_dir).map_err(|e| Error::FileIO {
+        action: FileIOAction::Create,
+        kind: FileKind::Directory,
+        path: out_dir.clone(),
+        err: Some(e.to_string()),
+    })?;
+
+    let mut command = Command::new("erlc");
+    let _ = command.arg("-o").arg(out_dir);
+    if profile.debug_info {
+        let _ = command.arg("+debug_info");
+    }
+    let output = command
+        .args(modules.iter().map(|m| m.erl_path(&profile.gen_dir)))
+        .args(erlang_files)
+        .output();
+
+    let output = match output {
+        Ok(output) => output,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Outcome::ErlcNotFound),
+        Err(e) => {
+            return Err(Error::ErlangCompilation {
+                module: None,
+                output: e.to_string(),
+            })
+        }
+    };
+
+    if output.status.success() {
+        return Ok(Outcome::Compiled);
+    }
+
+    // erlc reports errors on stdout, prefixed with the path of the file
+    let output = String::from_utf8_lossy(&output.stdout).to_string()
+        + String::from_utf8_lossy(&output.stderr).as_ref();
+    let module = failed_erl_module(&output).and_then(|name| {
+        modules
+            .iter()
+            .find(|m| m.name.join("@") == name)
+            .map(|m| (m.name.join("/"), m.path.clone()))
+    });
+    Err(Error::ErlangCompilation { module, output })
+}
+
+/// The name of the first Erlang module with an error in the output of erlc.
+///
+fn failed_erl_module(output: &str) -> Option<String> {
+    use regex::Regex;
+    lazy_static! {
+        static ref RE: Regex =
+            Regex::new(r"(?m)^(.*?)\.erl:\d+").expect("erlc::failed_erl_module() RE regex");
+    }
+
+    RE.captures(output)
+        .and_then(|captures| {
+            PathBuf::from(&captures[1])
+                .file_name()
+                .map(|n| n.to_owned())
+        })
+        .and_then(|name| name.to_str().map(|n| n.to_string()))
+}
+
+#[test]
+fn failed_erl_module_test() {
+    assert_eq!(failed_erl_module(""), None);
+    assert_eq!(
+        failed_erl_module("gen/src/one@two.erl:12: syntax error before: ')'\n"),
+        Some("one@two".to_string())
+    );
+    assert_eq!(
+        failed_erl_module("/app/gen/src/main.erl:3:5: function f/0 undefined\n"),
+        Some("main".to_string())
+    );
+    assert_eq!(
+        failed_erl_module("erlc: unknown option\nsrc/x.erl:1: bad\n"),
+        Some("x".to_string())
+    );
+}
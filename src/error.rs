@@ -1,5 +1,6 @@
 use crate::diagnostic::{
-    buffer_writer, write, write_project, Diagnostic, ProjectErrorDiagnostic, Severity,
+    buffer_writer, write, write_multi_file, write_project, Diagnostic, FileLabel,
+    MultiFileDiagnostic, ProjectErrorDiagnostic, Severity,
 };
 use crate::typ::pretty::Printer;
 use itertools::Itertools;
@@ -87,6 +88,11 @@ pub enum Error {
         second: PathBuf,
     },
 
+    InvalidModuleName {
+        path: PathBuf,
+        module: String,
+    },
+
     SrcImportingTest {
         path: PathBuf,
         src: Src,
@@ -95,8 +101,17 @@ pub enum Error {
         test_module: Name,
     },
 
+    ForbiddenImport {
+        path: PathBuf,
+        src: Src,
+        location: crate::ast::SrcSpan,
+        module: Name,
+        import: Name,
+        rule: crate::project::ImportRule,
+    },
+
     ImportCycle {
-        modules: Vec<Vec<String>>,
+        imports: Vec<CycleImport>,
     },
 
     FileIO {
@@ -111,9 +126,56 @@ pub enum Error {
         err: Option<std::io::ErrorKind>,
     },
 
+    UnknownProfile {
+        name: Name,
+        profiles: Vec<Name>,
+    },
+
+    WarningsAsErrors {
+        profile: Name,
+        count: usize,
+    },
+
+    ErlangCompilation {
+        module: Option<(Name, PathBuf)>,
+        output: String,
+    },
+
+    InvalidProjectConfig {
+        path: PathBuf,
+        src: Src,
+        location: Option<crate::ast::SrcSpan>,
+        error: String,
+    },
+
     Format {
         problem_files: Vec<crate::format::command::Formatted>,
     },
+
+    FormatLostComment {
+        path: PathBuf,
+        src: Src,
+        location: crate::ast::SrcSpan,
+    },
+
+    InvalidPackageInterface {
+        path: PathBuf,
+        error: String,
+    },
+
+    FormatRangeWithoutSingleFile,
+}
+
+/// An import statement that is one edge of an import cycle, with `module`
+/// importing `import`.
+///
+#[derive(Debug, PartialEq)]
+pub struct CycleImport {
+    pub module: Name,
+    pub import: Name,
+    pub path: PathBuf,
+    pub src: Src,
+    pub location: crate::ast::SrcSpan,
 }
 
 #[derive(Debug, PartialEq)]
@@ -133,10 +195,10 @@ impl StandardIOAction {
 pub enum FileIOAction {
     Open,
     Read,
-    Parse,
     Delete,
     Create,
     WriteTo,
+    Rename,
     FindParent,
 }
 
@@ -145,10 +207,10 @@ impl FileIOAction {
         match self {
             FileIOAction::Open => "open",
             FileIOAction::Read => "read",
-            FileIOAction::Parse => "parse",
             FileIOAction::Delete => "delete",
             FileIOAction::Create => "create",
             FileIOAction::WriteTo => "write to",
+            FileIOAction::Rename => "rename",
             FileIOAction::FindParent => "find the parent of",
         }
     }
@@ -222,6 +284,35 @@ cannot import them. Perhaps move the `{}` module to the src directory.",
                 .unwrap();
             }
 
+            Error::ForbiddenImport {
+                path,
+                src,
+                location,
+                module,
+                import,
+                rule,
+            } => {
+                let diagnostic = Diagnostic {
+                    title: "Forbidden import".to_string(),
+                    label: "Imported here".to_string(),
+                    file: path.to_str().unwrap().to_string(),
+                    src: src.to_string(),
+                    location: location.clone(),
+                };
+                write(buffer, diagnostic, Severity::Error);
+                writeln!(
+                    buffer,
+                    "The module `{}` is importing the module `{}`, but the
+forbidden_imports in gleam.toml do not permit modules in `{}`
+to import modules in `{}`.",
+                    module, import, rule.from, rule.to,
+                )
+                .unwrap();
+                if let Some(reason) = &rule.reason {
+                    writeln!(buffer, "\n{}", reason).unwrap();
+                }
+            }
+
             Error::DuplicateModule {
                 module,
                 first,
@@ -243,6 +334,24 @@ Second: {}
                 write_project(buffer, diagnostic);
             }
 
+            Error::InvalidModuleName { path, module } => {
+                let diagnostic = ProjectErrorDiagnostic {
+                    title: "Invalid module name".to_string(),
+                    label: format!(
+                        "The module `{}` defined in this file does not have a valid name:
+
+    {}
+
+Each part of a module name must start with a lowercase letter or underscore
+and may only contain lowercase letters, numbers, and underscores.
+",
+                        module,
+                        path.to_str().expect("pretty error print PathBuf to_str"),
+                    ),
+                };
+                write_project(buffer, diagnostic);
+            }
+
             Error::FileIO {
                 kind,
                 action,
@@ -1004,12 +1113,28 @@ but this one uses {}. Rewrite this using the fn({}) {{ ... }} syntax.",
                                 )
                                 .expect("error pretty buffer write");
                             }
+
+                            Error::InvalidClauseGuardModuleSelect { location } => {
+                                let diagnostic = Diagnostic {
+                                    title: "Invalid clause guard".to_string(),
+                                    label: "".to_string(),
+                                    file: path.to_str().unwrap().to_string(),
+                                    src: src.to_string(),
+                                    location: location.clone(),
+                                };
+                                write(buffer, diagnostic, Severity::Error);
+                                writeln!(
+                                    buffer,
+                                    "Only record constructors from imported modules can be used with the module.Constructor syntax."
+                                )
+                                .expect("error pretty buffer write");
+                            }
                         }
                     }
                 }
             }
 
-            Error::ImportCycle { modules } => import_cycle(buffer, modules.as_ref()),
+            Error::ImportCycle { imports } => import_cycle(buffer, imports.as_ref()),
 
             Error::UnknownImport {
                 module,
@@ -1058,6 +1183,104 @@ but it cannot be found.",
                 };
                 write_project(buffer, diagnostic);
             }
+            Error::UnknownProfile { name, profiles } => {
+                let diagnostic = ProjectErrorDiagnostic {
+                    title: "Unknown build profile".to_string(),
+                    label: format!(
+                        "There is no build profile named `{}`. The available profiles are:
+
+{}
+
+Profiles can be defined in the `profiles` section of gleam.toml.
+",
+                        name,
+                        profiles.iter().map(|p| format!("  - {}", p)).join("\n")
+                    ),
+                };
+                write_project(buffer, diagnostic);
+            }
+
+            Error::WarningsAsErrors { profile, count } => {
+                let diagnostic = ProjectErrorDiagnostic {
+                    title: "Warnings are not permitted".to_string(),
+                    label: format!(
+                        "The `{}` build profile treats warnings as errors and {} {} emitted.
+",
+                        profile,
+                        count,
+                        if *count == 1 {
+                            "warning was"
+                        } else {
+                            "warnings were"
+                        }
+                    ),
+                };
+                write_project(buffer, diagnostic);
+            }
+
+            Error::ErlangCompilation { module, output } => {
+                let source = match module {
+                    Some((name, path)) => format!(
+                        "generated for the module `{}` in {}",
+                        name,
+                        path.to_string_lossy()
+                    ),
+                    None => "generated for this project".to_string(),
+                };
+                let diagnostic = ProjectErrorDiagnostic {
+                    title: "Erlang compilation failed".to_string(),
+                    label: format!(
+                        "The Erlang code {} could not be compiled.
+This is a bug in the Gleam compiler, please report it.
+
+The output from erlc was:
+
+{}
+",
+                        source, output
+                    ),
+                };
+                write_project(buffer, diagnostic);
+            }
+
+            Error::InvalidProjectConfig {
+                path,
+                src,
+                location: Some(location),
+                error,
+            } => {
+                let diagnostic = Diagnostic {
+                    title: "Invalid project config".to_string(),
+                    label: error.clone(),
+                    file: path.to_string_lossy().to_string(),
+                    src: src.to_string(),
+                    location: location.clone(),
+                };
+                write(buffer, diagnostic, Severity::Error);
+            }
+
+            Error::InvalidProjectConfig {
+                path,
+                location: None,
+                error,
+                ..
+            } => {
+                let diagnostic = ProjectErrorDiagnostic {
+                    title: "Invalid project config".to_string(),
+                    label: format!(
+                        "The config in this file is not valid:
+
+    {}
+
+{}
+",
+                        path.to_string_lossy(),
+                        error
+                    ),
+                };
+                write_project(buffer, diagnostic);
+            }
+
             Error::Format { problem_files } => {
                 let mut files: Vec<_> = problem_files
                     .iter()
@@ -1075,6 +1298,57 @@ but it cannot be found.",
 
                 write_project(buffer, diagnostic);
             }
+
+            Error::FormatLostComment {
+                path,
+                src,
+                location,
+            } => {
+                let diagnostic = Diagnostic {
+                    title: "Comment lost when formatting".to_string(),
+                    label: "This comment".to_string(),
+                    file: path.to_str().unwrap().to_string(),
+                    src: src.to_string(),
+                    location: location.clone(),
+                };
+                write(buffer, diagnostic, Severity::Error);
+                writeln!(
+                    buffer,
+                    "The formatter was unable to place this comment in its output so
+the file has not been formatted. Please report this as a bug."
+                )
+                .unwrap();
+            }
+
+            Error::InvalidPackageInterface { path, error } => {
+                let diagnostic = ProjectErrorDiagnostic {
+                    title: "Invalid package interface".to_string(),
+                    label: format!(
+                        "The package interface in this file could not be read:
+
+    {}
+
+{}
+
+Package interfaces are written by `gleam export package-interface`.
+",
+                        path.to_string_lossy(),
+                        error
+                    ),
+                };
+                write_project(buffer, diagnostic);
+            }
+
+            Error::FormatRangeWithoutSingleFile => {
+                let diagnostic = ProjectErrorDiagnostic {
+                    title: "Range given for more than one file".to_string(),
+                    label: "A range is a byte range within a single file, so it can only be
+used when formatting one file or standard input.
+"
+                    .to_string(),
+                };
+                write_project(buffer, diagnostic);
+            }
         }
     }
 
@@ -1110,10 +1384,23 @@ fn std_io_error_kind_text(kind: &std::io::ErrorKind) -> String {
     }
 }
 
-fn import_cycle(buffer: &mut Buffer, modules: &[Vec<String>]) {
+fn import_cycle(buffer: &mut Buffer, imports: &[CycleImport]) {
     use std::io::Write;
     use termcolor::{Color, ColorSpec, WriteColor};
-    crate::diagnostic::write_title(buffer, "Import cycle");
+    let labels = imports
+        .iter()
+        .map(|import| FileLabel {
+            file: import.path.to_str().unwrap().to_string(),
+            src: import.src.clone(),
+            location: import.location.clone(),
+            label: format!("`{}` imports `{}`", import.module, import.import),
+        })
+        .collect();
+    let diagnostic = MultiFileDiagnostic {
+        title: "Import cycle".to_string(),
+        labels,
+    };
+    write_multi_file(buffer, diagnostic, Severity::Error);
     writeln!(
         buffer,
         "The import statements for these modules form a cycle:
@@ -1121,7 +1408,7 @@ fn import_cycle(buffer: &mut Buffer, modules: &[Vec<String>]) {
     ┌─────┐"
     )
     .unwrap();
-    for (index, name) in modules.iter().enumerate() {
+    for (index, import) in imports.iter().enumerate() {
         if index != 0 {
             writeln!(buffer, "    │     ↓").unwrap();
         }
@@ -1129,7 +1416,7 @@ fn import_cycle(buffer: &mut Buffer, modules: &[Vec<String>]) {
         buffer
             .set_color(ColorSpec::new().set_fg(Some(Color::Cyan)))
             .unwrap();
-        writeln!(buffer, "{}", name.join("/")).unwrap();
+        writeln!(buffer, "{}", import.module).unwrap();
         buffer.set_color(&ColorSpec::new()).unwrap();
     }
     writeln!(
@@ -0,0 +1,361 @@
+//! Automatic migration of deprecated syntax.
+//!
+//! Each `Rule` finds uses of a deprecated construct in a parsed module. The
+//! formatter only ever prints the current syntax, so the top level statements
+//! containing those uses are reprinted with the formatter, leaving the rest of
+//! the file unchanged.
+
+use crate::{
+    ast::*,
+    error::{Error, FileIOAction, FileKind},
+    format::command::Formatted,
+    line_numbers::LineNumbers,
+    project::FormatConfig,
+};
+use std::path::PathBuf;
+
+pub trait Rule {
+    /// What the rewrite does, printed for each use that is fixed.
+    fn description(&self) -> &'static str;
+
+    fn check_expr(&self, _expr: &UntypedExpr) -> Option<SrcSpan> {
+        None
+    }
+
+    fn check_pattern(&self, _pattern: &UntypedPattern) -> Option<SrcSpan> {
+        None
+    }
+}
+
+/// All the rules applied by `gleam fix`. New deprecations register their
+/// rewrite here.
+///
+pub fn rules() -> Vec<Box<dyn Rule>> {
+    vec![Box::new(ListPrepend)]
+}
+
+/// `[x | xs]` is rewritten to `[x, ..xs]`.
+///
+struct ListPrepend;
+
+impl Rule for ListPrepend {
+    fn description(&self) -> &'static str {
+        "Rewrote `[x | xs]` to `[x, ..xs]`"
+    }
+
+    // Each element of a list literal is a cons cell with the deprecated
+    // syntax, so only the last one is reported
+    fn check_expr(&self, expr: &UntypedExpr) -> Option<SrcSpan> {
+        match expr {
+            UntypedExpr::ListCons {
+                deprecated_syntax: true,
+                tail,
+                location,
+                ..
+            } => match tail.as_ref() {
+                UntypedExpr::ListCons {
+                    deprecated_syntax: true,
+                    ..
+                } => None,
+                _ => Some(location.clone()),
+            },
+            _ => None,
+        }
+    }
+
+    fn check_pattern(&self, pattern: &UntypedPattern) -> Option<SrcSpan> {
+        match pattern {
+            Pattern::Cons {
+                deprecated_syntax: true,
+                tail,
+                location,
+                ..
+            } => match tail.as_ref() {
+                Pattern::Cons {
+                    deprecated_syntax: true,
+                    ..
+                } => None,
+                _ => Some(location.clone()),
+            },
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Fix {
+    pub description: &'static str,
+    pub location: SrcSpan,
+}
+
+/// Rewrite all uses of deprecated syntax in the source, returning the new
+/// source and the fixes made.
+///
+pub fn fix(
+    src: &str,
+    rules: &[Box<dyn Rule>],
+    config: &FormatConfig,
+) -> Result<(String, Vec<Fix>), crate::parser::LalrpopError> {
+    let (stripped_src, _) = crate::parser::strip_extra(src);
+    let module = crate::grammar::ModuleParser::new()
+        .parse(&stripped_src)
+        .map_err(|e| e.map_token(|crate::grammar::Token(a, b)| (a, b.to_string())))?;
+
+    let mut fixes = vec![];
+    for statement in module.statements.iter() {
+        if let Statement::Fn { body, .. } = statement {
+            find_in_expr(body, rules, &mut fixes);
+        }
+    }
+
+    if fixes.is_empty() {
+        return Ok((src.to_string(), fixes));
+    }
+    let ranges: Vec<_> = fixes
+        .iter()
+        .map(|fix| fix.location.start..fix.location.end)
+        .collect();
+    let output = crate::format::pretty_ranges(src, &ranges, config)?;
+    Ok((output, fixes))
+}
+
+fn find_in_expr(expr: &UntypedExpr, rules: &[Box<dyn Rule>], fixes: &mut Vec<Fix>) {
+    fixes.extend(rules.iter().filter_map(|rule| {
+        rule.check_expr(expr).map(|location| Fix {
+            description: rule.description(),
+            location,
+        })
+    }));
+
+    match expr {
+        UntypedExpr::Int { .. }
+        | UntypedExpr::Float { .. }
+        | UntypedExpr::String { .. }
+        | UntypedExpr::Var { .. }
+        | UntypedExpr::ListNil { .. }
+        | UntypedExpr::Todo { .. }
+        | UntypedExpr::Panic { .. } => (),
+
+        UntypedExpr::Fn { body, .. } => find_in_expr(body, rules, fixes),
+
+        UntypedExpr::NegateInt { value, .. }
+        | UntypedExpr::NegateFloat { value, .. }
+        | UntypedExpr::NotBool { value, .. } => find_in_expr(value, rules, fixes),
+
+        UntypedExpr::FieldAccess { container, .. } => find_in_expr(container, rules, fixes),
+
+        UntypedExpr::TupleIndex { tuple, .. } => find_in_expr(tuple, rules, fixes),
+
+        UntypedExpr::Seq {
+            first: left,
+            then: right,
+        }
+        | UntypedExpr::ListCons {
+            head: left,
+            tail: right,
+            ..
+        }
+        | UntypedExpr::BinOp { left, right, .. }
+        | UntypedExpr::Pipe { left, right, .. } => {
+            find_in_expr(left, rules, fixes);
+            find_in_expr(right, rules, fixes);
+        }
+
+        UntypedExpr::Call { fun, args, .. } => {
+            find_in_expr(fun, rules, fixes);
+            for arg in args {
+                find_in_expr(&arg.value, rules, fixes);
+            }
+        }
+
+        UntypedExpr::Let {
+            value,
+            pattern,
+            then,
+            ..
+        } => {
+            find_in_expr(value, rules, fixes);
+            find_in_pattern(pattern, rules, fixes);
+            find_in_expr(then, rules, fixes);
+        }
+
+        UntypedExpr::Case {
+            subjects, clauses, ..
+        } => {
+            for subject in subjects {
+                find_in_expr(subject, rules, fixes);
+            }
+            for clause in clauses {
+                for pattern in std::iter::once(&clause.pattern)
+                    .chain(clause.alternative_patterns.iter())
+                    .flatten()
+                {
+                    find_in_pattern(pattern, rules, fixes);
+                }
+                find_in_expr(&clause.then, rules, fixes);
+            }
+        }
+
+        UntypedExpr::Tuple { elems, .. } => {
+            for elem in elems {
+                find_in_expr(elem, rules, fixes);
+            }
+        }
+    }
+}
+
+fn find_in_pattern(pattern: &UntypedPattern, rules: &[Box<dyn Rule>], fixes: &mut Vec<Fix>) {
+    fixes.extend(rules.iter().filter_map(|rule| {
+        rule.check_pattern(pattern).map(|location| Fix {
+            description: rule.description(),
+            location,
+        })
+    }));
+
+    match pattern {
+        Pattern::Int { .. }
+        | Pattern::Float { .. }
+        | Pattern::String { .. }
+        | Pattern::Var { .. }
+        | Pattern::Discard { .. }
+        | Pattern::Nil { .. } => (),
+
+        Pattern::Let { pattern, .. } => find_in_pattern(pattern, rules, fixes),
+
+        Pattern::Cons { head, tail, .. } => {
+            find_in_pattern(head, rules, fixes);
+            find_in_pattern(tail, rules, fixes);
+        }
+
+        Pattern::Constructor { args, .. } => {
+            for arg in args {
+                find_in_pattern(&arg.value, rules, fixes);
+            }
+        }
+
+        Pattern::Tuple { elems, .. } => {
+            for elem in elems {
+                find_in_pattern(elem, rules, fixes);
+            }
+        }
+    }
+}
+
+/// Fix the deprecated syntax in the src and test directories of the project.
+///
+pub fn run(root: &str, config: &FormatConfig) -> Result<(), Error> {
+    let root = PathBuf::from(root);
+    let rules = rules();
+    let mut fixed_files = vec![];
+
+    let paths: Vec<_> = ["src", "test"]
+        .iter()
+        .flat_map(|dir| crate::project::gleam_files(&root.join(dir)).collect::<Vec<_>>())
+        .collect();
+
+    for path in paths {
+        let src = std::fs::read_to_string(&path).map_err(|e| Error::FileIO {
+            action: FileIOAction::Read,
+            kind: FileKind::File,
+            path: path.clone(),
+            err: Some(e.to_string()),
+        })?;
+
+        let (output, fixes) = fix(&src, &rules, config).map_err(|error| Error::Parse {
+            path: path.clone(),
+            src: src.clone(),
+            error,
+        })?;
+        if fixes.is_empty() {
+            continue;
+        }
+
+        if let Some(location) = crate::format::lost_comment(&src, &output) {
+            return Err(Error::FormatLostComment {
+                path,
+                src,
+                location,
+            });
+        }
+
+        let line_numbers = LineNumbers::new(&src);
+        for fix in fixes {
+            println!(
+                "{}:{}: {}",
+                path.to_string_lossy(),
+                line_numbers.line_number(fix.location.start),
+                fix.description
+            );
+        }
+
+        fixed_files.push(Formatted {
+            path,
+            input: src,
+            output,
+        });
+    }
+
+    crate::format::command::write_formatted(fixed_files)
+}
+
+#[test]
+fn fix_test() {
+    let config = FormatConfig::default();
+    let rules = rules();
+
+    let src = "fn main() {
+  [1, 2 | x]
+}
+
+fn other(x) {   [x | []] }
+
+fn unchanged() {   [1, ..x] }
+";
+    let (output, fixes) = fix(src, &rules, &config).unwrap();
+    assert_eq!(
+        "fn main() {
+  [1, 2, ..x]
+}
+
+fn other(x) {
+  [x]
+}
+
+fn unchanged() {   [1, ..x] }
+",
+        output
+    );
+    assert_eq!(
+        vec![
+            SrcSpan { start: 22, end: 23 },
+            SrcSpan { start: 49, end: 51 }
+        ],
+        fixes.into_iter().map(|f| f.location).collect::<Vec<_>>()
+    );
+
+    let src = "fn main(x) {
+  case x {
+    [] -> 0
+    [a, b | rest] -> a
+  }
+}
+";
+    let (output, fixes) = fix(src, &rules, &config).unwrap();
+    assert_eq!(
+        "fn main(x) {
+  case x {
+    [] -> 0
+    [a, b, ..rest] -> a
+  }
+}
+",
+        output
+    );
+    assert_eq!(1, fixes.len());
+
+    let src = "fn main() {   [1, ..x] }\n";
+    assert_eq!(
+        (src.to_string(), vec![]),
+        fix(src, &rules, &config).unwrap()
+    );
+}
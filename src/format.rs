@@ -7,6 +7,7 @@ use crate::{
     ast::*,
     parser::{Comment, ModuleComments},
     pretty::*,
+    project::FormatConfig,
     typ::{self, Type},
 };
 use itertools::Itertools;
@@ -14,21 +15,180 @@ use std::sync::Arc;
 
 const INDENT: isize = 2;
 
-pub fn pretty(src: &str) -> Result<String, crate::parser::LalrpopError> {
+pub fn pretty_with_config(
+    src: &str,
+    config: &FormatConfig,
+) -> Result<String, crate::parser::LalrpopError> {
     let (stripped_src, comments) = crate::parser::strip_extra(src.as_ref());
     let ast = crate::grammar::ModuleParser::new()
         .parse(&stripped_src)
         .map_err(|e| e.map_token(|crate::grammar::Token(a, b)| (a, b.to_string())))?;
     let mut formatter = Formatter::with_comments(&comments);
+    formatter.config = config.clone();
     Ok(pretty_module(&ast, &mut formatter))
 }
 
+/// Format only the top level statements that overlap the given byte range,
+/// leaving the rest of the source unchanged. An empty range formats the
+/// statement at that position, if any.
+///
+pub fn pretty_range(
+    src: &str,
+    range: &std::ops::Range<usize>,
+    config: &FormatConfig,
+) -> Result<String, crate::parser::LalrpopError> {
+    pretty_ranges(src, std::slice::from_ref(range), config)
+}
+
+/// Format only the top level statements that overlap any of the given byte
+/// ranges, leaving the rest of the source unchanged.
+///
+pub fn pretty_ranges(
+    src: &str,
+    ranges: &[std::ops::Range<usize>],
+    config: &FormatConfig,
+) -> Result<String, crate::parser::LalrpopError> {
+    let (stripped_src, comments) = crate::parser::strip_extra(src);
+    let ast = crate::grammar::ModuleParser::new()
+        .parse(&stripped_src)
+        .map_err(|e| e.map_token(|crate::grammar::Token(a, b)| (a, b.to_string())))?;
+
+    let mut output = String::with_capacity(src.len());
+    let mut copied_to = 0;
+    let mut previous_end = 0;
+
+    for (statement, (start, end)) in ast
+        .statements
+        .iter()
+        .zip(statement_spans(&stripped_src, &ast.statements))
+    {
+        if !ranges.iter().any(|r| start <= r.end && r.start <= end) {
+            previous_end = end;
+            continue;
+        }
+
+        // Comments on the lines between the previous statement and this one
+        // belong to this statement, so they are formatted along with it.
+        let first_line = match src[previous_end..].find('\n') {
+            Some(i) if previous_end > 0 => previous_end + i + 1,
+            _ => previous_end,
+        };
+        let region_start = comments
+            .comments
+            .iter()
+            .chain(comments.doc_comments.iter())
+            .map(|c| c.start)
+            .filter(|c| *c >= first_line && *c < start)
+            .min()
+            .unwrap_or(start);
+        let region_start = src[..region_start].rfind('\n').map_or(0, |i| i + 1);
+
+        let mut formatter = Formatter {
+            comments: within(&comments.comments, |c| c.start, region_start, end),
+            doc_comments: within(&comments.doc_comments, |c| c.start, region_start, end),
+            empty_lines: within(&comments.empty_lines, |p| *p, region_start, end),
+            module_comments: &[],
+            config: config.clone(),
+        };
+        let doc = formatter
+            .top_level_statement(statement)
+            .append(formatter.remaining_comments());
+
+        output.push_str(&src[copied_to..region_start]);
+        output.push_str(&format(config.line_width as isize, doc));
+        copied_to = end;
+        previous_end = end;
+    }
+
+    output.push_str(&src[copied_to..]);
+    Ok(output)
+}
+
+/// The byte spans of the code of each statement, from the start of any
+/// attribute to the end of the last token. The location of an import starts
+/// after the `import` keyword, so the keyword is included here.
+///
+fn statement_spans(stripped_src: &str, statements: &[UntypedStatement]) -> Vec<(usize, usize)> {
+    let starts: Vec<_> = statements
+        .iter()
+        .map(|statement| {
+            let start = statement.location().start;
+            let keyword = match statement {
+                Statement::Import { .. } => "import",
+                _ if statement.deprecation().is_some() => "@deprecated",
+                _ => return start,
+            };
+            stripped_src[..start].rfind(keyword).unwrap_or(start)
+        })
+        .collect();
+
+    starts
+        .iter()
+        .enumerate()
+        .map(|(i, start)| {
+            let next_start = starts.get(i + 1).cloned().unwrap_or(stripped_src.len());
+            (
+                *start,
+                start + stripped_src[*start..next_start].trim_end().len(),
+            )
+        })
+        .collect()
+}
+
+/// The sub-slice of sorted items that have a position within a range.
+///
+fn within<T>(items: &[T], position: impl Fn(&T) -> usize, start: usize, end: usize) -> &[T] {
+    let first = items
+        .iter()
+        .position(|i| position(i) >= start)
+        .unwrap_or(items.len());
+    let last = items
+        .iter()
+        .position(|i| position(i) >= end)
+        .unwrap_or(items.len());
+    &items[first..last.max(first)]
+}
+
+/// Find a comment in the source that is missing from the formatted output,
+/// returning its location. Formatted output is only to be used when this
+/// returns `None`.
+///
+pub fn lost_comment(src: &str, formatted: &str) -> Option<SrcSpan> {
+    let (_, before) = crate::parser::strip_extra(src);
+    let (_, after) = crate::parser::strip_extra(formatted);
+    let mut remaining: Vec<_> = after
+        .comments
+        .iter()
+        .chain(after.doc_comments.iter())
+        .map(|c| c.content)
+        .collect();
+
+    before
+        .comments
+        .iter()
+        .chain(before.doc_comments.iter())
+        .find(
+            |comment| match remaining.iter().position(|c| *c == comment.content) {
+                Some(index) => {
+                    remaining.swap_remove(index);
+                    false
+                }
+                None => true,
+            },
+        )
+        .map(|comment| SrcSpan {
+            start: comment.start,
+            end: comment.start + comment.content.len() + 2,
+        })
+}
+
 #[derive(Debug, Clone)]
 pub struct Formatter<'a> {
     comments: &'a [Comment<'a>],
     doc_comments: &'a [Comment<'a>],
     module_comments: &'a [&'a str],
     empty_lines: &'a [usize],
+    config: FormatConfig,
 }
 
 impl<'a> Formatter<'a> {
@@ -38,6 +198,7 @@ impl<'a> Formatter<'a> {
             doc_comments: &[],
             module_comments: &[],
             empty_lines: &[],
+            config: FormatConfig::default(),
         }
     }
 
@@ -47,6 +208,7 @@ impl<'a> Formatter<'a> {
             doc_comments: comments.doc_comments.as_slice(),
             empty_lines: comments.empty_lines.as_slice(),
             module_comments: comments.module_comments.as_slice(),
+            config: FormatConfig::default(),
         }
     }
 
@@ -64,6 +226,16 @@ impl<'a> Formatter<'a> {
         popped
     }
 
+    // Pop comments that occur before a byte-index in the source, rendering
+    // them as lines to be placed at the end of an indented block
+    fn trailing_comments(&mut self, limit: usize) -> Option<Document> {
+        let mut comments = self.pop_comments(limit).peekable();
+        comments.peek()?;
+        Some(force_break().append(concat(
+            comments.map(|c| line().append("//").append(c.to_string())),
+        )))
+    }
+
     fn pop_empty_lines(&mut self, limit: usize) -> bool {
         let mut end = 0;
         for (i, postition) in self.empty_lines.iter().enumerate() {
@@ -83,26 +255,44 @@ impl<'a> Formatter<'a> {
         let mut imports = Vec::new();
         let mut declarations = Vec::with_capacity(module.statements.len());
 
+        // Imports separated by an empty line are sorted as separate groups
+        let mut import_group = 0;
+
         for statement in module.statements.iter() {
-            let start = statement.location().start;
             match statement {
-                Statement::Import { .. } => {
+                Statement::Import {
+                    module, location, ..
+                } => {
+                    if self.pop_empty_lines(location.start) && has_imports {
+                        import_group += 1;
+                    }
                     has_imports = true;
-                    let comments = self.pop_comments(start);
-                    let statement = self.statement(statement);
-                    imports.push(commented(statement, comments))
+                    let doc = self.top_level_statement(statement);
+                    imports.push((import_group, module, doc))
                 }
 
                 _other => {
                     has_declarations = true;
-                    let comments = self.pop_comments(start);
-                    let declaration = self.documented_statement(statement);
-                    declarations.push(commented(declaration, comments))
+                    declarations.push(self.top_level_statement(statement))
                 }
             }
         }
 
-        let imports = concat(imports.into_iter().intersperse(line()));
+        if self.config.sort_imports {
+            imports.sort_by(|(group_a, module_a, _), (group_b, module_b, _)| {
+                (group_a, module_a).cmp(&(group_b, module_b))
+            });
+        }
+        let mut previous_group = 0;
+        let imports = concat(imports.into_iter().enumerate().map(|(i, (group, _, doc))| {
+            let sep = match i {
+                0 => nil(),
+                _ if self.config.sort_imports && group != previous_group => lines(2),
+                _ => line(),
+            };
+            previous_group = group;
+            sep.append(doc)
+        }));
         let declarations = concat(declarations.into_iter().intersperse(lines(2)));
 
         let sep = if has_imports && has_declarations {
@@ -111,17 +301,6 @@ impl<'a> Formatter<'a> {
             nil()
         };
 
-        let doc_comments = concat(
-            self.doc_comments
-                .into_iter()
-                .map(|comment| line().append("///").append(comment.content)),
-        );
-        let comments = concat(
-            self.comments
-                .into_iter()
-                .map(|comment| line().append("//").append(comment.content)),
-        );
-
         let module_comments = if !self.module_comments.is_empty() {
             let comments = self
                 .module_comments
@@ -136,11 +315,34 @@ impl<'a> Formatter<'a> {
             .append(imports)
             .append(sep)
             .append(declarations)
-            .append(doc_comments)
-            .append(comments)
+            .append(self.remaining_comments())
             .append(line())
     }
 
+    fn top_level_statement(&mut self, statement: &UntypedStatement) -> Document {
+        let comments = self.pop_comments(statement.location().start);
+        let doc = match statement {
+            Statement::Import { .. } => self.statement(statement),
+            _ => self.documented_statement(statement),
+        };
+        commented(doc, comments)
+    }
+
+    // Comments that were not placed before any statement
+    fn remaining_comments(&self) -> Document {
+        let doc_comments = concat(
+            self.doc_comments
+                .iter()
+                .map(|comment| line().append("///").append(comment.content)),
+        );
+        let comments = concat(
+            self.comments
+                .iter()
+                .map(|comment| line().append("//").append(comment.content)),
+        );
+        doc_comments.append(comments)
+    }
+
     fn statement(&mut self, statement: &UntypedStatement) -> Document {
         match statement {
             Statement::Fn {
@@ -149,8 +351,9 @@ impl<'a> Formatter<'a> {
                 body,
                 public,
                 return_annotation,
+                end_position,
                 ..
-            } => self.fn_(public, name, args, return_annotation, body),
+            } => self.fn_(public, name, args, return_annotation, body, *end_position),
 
             Statement::TypeAlias {
                 alias,
@@ -193,32 +396,48 @@ impl<'a> Formatter<'a> {
                 as_name,
                 unqualified,
                 ..
-            } => nil()
-                .append("import ")
-                .append(module.join("/"))
-                .append(if unqualified.is_empty() {
-                    nil()
-                } else {
-                    ".{".to_doc()
-                        .append(concat(
-                            unqualified
-                                .iter()
-                                .map(|e| e.clone().to_doc())
-                                .intersperse(", ".to_doc()),
-                        ))
-                        .append("}")
-                })
-                .append(if let Some(name) = as_name {
-                    format!(" as {}", name).to_doc()
-                } else {
-                    nil()
-                }),
+            } => {
+                let mut unqualified: Vec<_> = unqualified.iter().collect();
+                if self.config.sort_imports {
+                    unqualified.sort_by(|a, b| a.name.cmp(&b.name));
+                }
+                nil()
+                    .append("import ")
+                    .append(module.join("/"))
+                    .append(if unqualified.is_empty() {
+                        nil()
+                    } else {
+                        ".{".to_doc()
+                            .append(concat(
+                                unqualified
+                                    .iter()
+                                    .map(|&e| e.clone().to_doc())
+                                    .intersperse(", ".to_doc()),
+                            ))
+                            .append("}")
+                    })
+                    .append(if let Some(name) = as_name {
+                        format!(" as {}", name).to_doc()
+                    } else {
+                        nil()
+                    })
+            }
         }
     }
 
     fn documented_statement(&mut self, s: &UntypedStatement) -> Document {
         let comments = self.doc_comments(s.location().start);
-        comments.append(self.statement(s)).group()
+        let deprecation = match s.deprecation() {
+            None => nil(),
+            Some(message) => format!("@deprecated(\"{}\")", message)
+                .to_doc()
+                .append(force_break())
+                .append(line()),
+        };
+        comments
+            .append(deprecation)
+            .append(self.statement(s))
+            .group()
     }
 
     fn doc_comments(&mut self, limit: usize) -> Document {
@@ -320,8 +539,9 @@ impl<'a> Formatter<'a> {
         args: &Vec<UntypedArg>,
         return_annotation: &Option<TypeAst>,
         body: &UntypedExpr,
+        end_position: usize,
     ) -> Document {
-        pub_(*public)
+        let head = pub_(*public)
             .append("fn ")
             .append(name)
             .append(self.fn_args(args))
@@ -329,9 +549,14 @@ impl<'a> Formatter<'a> {
                 " -> ".to_doc().append(self.type_ast(anno))
             } else {
                 nil()
-            })
-            .append(" {")
-            .append(line().append(self.expr(body)).nest(INDENT).group())
+            });
+        let body = self.expr(body);
+        let body = match self.trailing_comments(end_position) {
+            Some(comments) => body.append(comments),
+            None => body,
+        };
+        head.append(" {")
+            .append(line().append(body).nest(INDENT).group())
             .append(line())
             .append("}")
     }
@@ -358,12 +583,17 @@ impl<'a> Formatter<'a> {
         args: &[UntypedArg],
         return_annotation: Option<&TypeAst>,
         body: &UntypedExpr,
+        end_position: usize,
     ) -> Document {
         let args = self.fn_args(args);
         let body = match body {
             UntypedExpr::Case { .. } => force_break().append(self.expr(body)),
             _ => self.expr(body),
         };
+        let body = match self.trailing_comments(end_position) {
+            Some(comments) => body.append(comments),
+            None => body,
+        };
 
         let header = "fn".to_doc().append(args);
 
@@ -384,9 +614,19 @@ impl<'a> Formatter<'a> {
     }
 
     fn seq(&mut self, first: &UntypedExpr, then: &UntypedExpr) -> Document {
+        let first_doc = self.expr(first);
+        self.seq_with_first(first_doc, first, then)
+    }
+
+    fn seq_with_first(
+        &mut self,
+        first_doc: Document,
+        first: &UntypedExpr,
+        then: &UntypedExpr,
+    ) -> Document {
         force_break()
             .append({
-                let doc = self.expr(first).group();
+                let doc = first_doc.group();
                 self.pop_empty_lines(first.location().end);
                 doc
             })
@@ -395,12 +635,34 @@ impl<'a> Formatter<'a> {
             } else {
                 line()
             })
-            .append(self.expr(then))
+            .append(self.following_expr(then))
+    }
+
+    /// An expression that directly follows another cannot start with a unary
+    /// `-` or `-.` as it would be parsed as subtraction, so it is wrapped in
+    /// braces when needed.
+    ///
+    fn following_expr(&mut self, expr: &UntypedExpr) -> Document {
+        match expr {
+            UntypedExpr::Seq { first, then } if starts_with_negate(first) => {
+                let comments = self.pop_comments(expr.start_byte_index());
+                let first_doc = self.expr(first).surround("{ ", " }");
+                commented(self.seq_with_first(first_doc, first, then), comments)
+            }
+
+            _ if starts_with_negate(expr) => {
+                let comments = self.pop_comments(expr.start_byte_index());
+                commented(self.expr(expr).surround("{ ", " }"), comments)
+            }
+
+            _ => self.expr(expr),
+        }
     }
 
     fn let_(
         &mut self,
         pattern: &UntypedPattern,
+        annotation: Option<&TypeAst>,
         value: &UntypedExpr,
         then: &UntypedExpr,
         assert: bool,
@@ -415,17 +677,23 @@ impl<'a> Formatter<'a> {
         force_break()
             .append(if assert { "assert " } else { "let " })
             .append(self.pattern(pattern))
+            .append(match annotation {
+                Some(a) => ": ".to_doc().append(self.type_ast(a)),
+                None => nil(),
+            })
             .append(" = ")
             .append(self.hanging_expr(value))
             .append(line)
-            .append(self.expr(then))
+            .append(self.following_expr(then))
     }
 
     fn expr(&mut self, expr: &UntypedExpr) -> Document {
         let comments = self.pop_comments(expr.start_byte_index());
 
         let document = match expr {
-            UntypedExpr::Todo { .. } => "todo".to_doc(),
+            UntypedExpr::Todo { message, .. } => error_expr("todo", message),
+
+            UntypedExpr::Panic { message, .. } => error_expr("panic", message),
 
             UntypedExpr::Pipe { left, right, .. } => self.pipe(left, right),
 
@@ -454,61 +722,102 @@ impl<'a> Formatter<'a> {
                 return_annotation,
                 args,
                 body,
+                location,
                 ..
-            } => self.expr_fn(args.as_slice(), return_annotation.as_ref(), body.as_ref()),
-
-            UntypedExpr::ListNil { .. } => "[]".to_doc(),
+            } => self.expr_fn(
+                args.as_slice(),
+                return_annotation.as_ref(),
+                body.as_ref(),
+                location.end,
+            ),
 
-            UntypedExpr::ListCons { head, tail, .. } => self.list_cons(head, tail),
+            UntypedExpr::ListNil { location } => match self.trailing_comments(location.end) {
+                Some(comments) => "["
+                    .to_doc()
+                    .append(comments.nest(INDENT))
+                    .append(line())
+                    .append("]"),
+                None => "[]".to_doc(),
+            },
+
+            UntypedExpr::ListCons {
+                head,
+                tail,
+                location,
+                ..
+            } => self.list_cons(head, tail, location.end),
 
-            UntypedExpr::Call { fun, args, .. } => self
-                .expr(fun)
-                .append(wrap_args(args.iter().map(|a| self.call_arg(a)))),
+            UntypedExpr::Call {
+                fun,
+                args,
+                location,
+            } => {
+                let fun = self.expr(fun);
+                let args: Vec<_> = args.iter().map(|a| self.call_arg(a)).collect();
+                let comments = self.trailing_comments(location.end);
+                fun.append(wrap_args_commented(args.into_iter(), comments))
+            }
 
             UntypedExpr::BinOp {
                 name, left, right, ..
             } => self.bin_op(name, left, right),
 
+            UntypedExpr::NegateInt { value, .. } => self.unary_op("-", value),
+
+            UntypedExpr::NegateFloat { value, .. } => self.unary_op("-.", value),
+
+            UntypedExpr::NotBool { value, .. } => self.unary_op("!", value),
+
             UntypedExpr::Let {
                 value,
                 pattern,
+                annotation,
                 then,
                 assert,
                 ..
-            } => self.let_(pattern, value, then, *assert),
+            } => self.let_(pattern, annotation.as_ref(), value, then, *assert),
 
             UntypedExpr::Case {
-                subjects, clauses, ..
-            } => "case "
-                .to_doc()
-                .append(concat(
+                subjects,
+                clauses,
+                location,
+            } => {
+                let subjects = concat(
                     subjects
                         .into_iter()
                         .map(|s| self.expr(s))
                         .intersperse(", ".to_doc()),
-                ))
-                .append(" {")
-                .append(
-                    line()
-                        .append(force_break())
-                        .append(concat(
-                            clauses
-                                .into_iter()
-                                .map(|c| self.clause(c))
-                                .intersperse(lines(1)),
-                        ))
-                        .nest(INDENT),
-                )
-                .append(line())
-                .append("}"),
+                );
+                let clauses = concat(
+                    clauses
+                        .into_iter()
+                        .map(|c| self.clause(c))
+                        .intersperse(lines(1)),
+                );
+                let clauses = match self.trailing_comments(location.end) {
+                    Some(comments) => clauses.append(comments),
+                    None => clauses,
+                };
+                "case "
+                    .to_doc()
+                    .append(subjects)
+                    .append(" {")
+                    .append(line().append(force_break()).append(clauses).nest(INDENT))
+                    .append(line())
+                    .append("}")
+            }
 
             UntypedExpr::FieldAccess {
                 label, container, ..
             } => self.expr(container).append(".").append(label.clone()),
 
-            UntypedExpr::Tuple { elems, .. } => "tuple"
-                .to_doc()
-                .append(wrap_args(elems.iter().map(|e| self.wrap_expr(e)))),
+            UntypedExpr::Tuple { elems, location } => {
+                let elems: Vec<_> = elems.iter().map(|e| self.wrap_expr(e)).collect();
+                let comments = self.trailing_comments(location.end);
+                "tuple"
+                    .to_doc()
+                    .append(wrap_args_commented(elems.into_iter(), comments))
+            }
         };
         commented(document, comments)
     }
@@ -524,6 +833,12 @@ impl<'a> Formatter<'a> {
             .append(self.operator_side(right, precedence, right_precedence))
     }
 
+    fn unary_op(&mut self, op: &'static str, value: &UntypedExpr) -> Document {
+        let precedence = value.binop_precedence();
+        let value = self.expr(value);
+        op.to_doc().append(self.operator_side(value, 9, precedence))
+    }
+
     pub fn operator_side(&mut self, doc: Document, op: u8, side: u8) -> Document {
         if op > side {
             delim("{")
@@ -736,9 +1051,11 @@ impl<'a> Formatter<'a> {
                 .append(force_break())
                 .append("}"),
 
-            UntypedExpr::Call { .. } | UntypedExpr::Fn { .. } | UntypedExpr::Case { .. } => {
-                self.expr(expr)
-            }
+            UntypedExpr::Call { .. }
+            | UntypedExpr::Fn { .. }
+            | UntypedExpr::Case { .. }
+            | UntypedExpr::ListCons { .. }
+            | UntypedExpr::Tuple { .. } => self.expr(expr),
 
             _ => self.expr(expr).nest(INDENT),
         }
@@ -770,7 +1087,7 @@ impl<'a> Formatter<'a> {
             })
     }
 
-    fn list_cons(&mut self, head: &UntypedExpr, tail: &UntypedExpr) -> Document {
+    fn list_cons(&mut self, head: &UntypedExpr, tail: &UntypedExpr, end: usize) -> Document {
         let (elems, tail) = list_cons(head, tail, categorise_list_expr);
         let elems = concat(
             elems
@@ -779,7 +1096,13 @@ impl<'a> Formatter<'a> {
                 .intersperse(delim(",")),
         );
         let tail = tail.map(|e| self.expr(e));
-        list(elems, tail)
+        // A list with a tail has the location of its tail
+        let comments = if tail.is_none() {
+            self.trailing_comments(end)
+        } else {
+            None
+        };
+        list(elems, tail, comments)
     }
 
     fn pattern(&mut self, pattern: &UntypedPattern) -> Document {
@@ -812,7 +1135,7 @@ impl<'a> Formatter<'a> {
                         .intersperse(delim(",")),
                 );
                 let tail = tail.map(|e| self.pattern(e));
-                list(elems, tail)
+                list(elems, tail, None)
             }
 
             Pattern::Constructor {
@@ -895,7 +1218,8 @@ impl<'a> Formatter<'a> {
 }
 
 pub fn pretty_module(m: &UntypedModule, formatter: &mut Formatter<'_>) -> String {
-    format(80, formatter.module(m))
+    let line_width = formatter.config.line_width as isize;
+    format(line_width, formatter.module(m))
 }
 
 impl Documentable for &ArgNames {
@@ -958,6 +1282,7 @@ impl Documentable for &BinOp {
             BinOp::DivInt => " / ",
             BinOp::DivFloat => " /. ",
             BinOp::ModuloInt => " % ",
+            BinOp::Concatenate => " <> ",
         }
         .to_doc()
     }
@@ -1023,8 +1348,81 @@ impl Documentable for &UntypedClauseGuard {
 
             ClauseGuard::Float { value, .. } => value.to_string().to_doc(),
 
+            ClauseGuard::String { value, .. } => value.clone().to_doc().surround("\"", "\""),
+
             ClauseGuard::Var { name, .. } => name.to_string().to_doc(),
+
+            ClauseGuard::Tuple { elems, .. } => "tuple"
+                .to_doc()
+                .append(wrap_args(elems.iter().map(|e| e.to_doc()))),
+
+            ClauseGuard::TupleIndex { tuple, index, .. } => {
+                clause_guard_container(tuple).append(".").append(*index)
+            }
+
+            ClauseGuard::FieldAccess {
+                container, label, ..
+            } => clause_guard_container(container)
+                .append(".")
+                .append(label.to_string()),
+
+            ClauseGuard::Constructor {
+                module, name, args, ..
+            } => {
+                let name = match module {
+                    Some(m) => m.to_string().to_doc().append(".").append(name.to_string()),
+                    None => name.to_string().to_doc(),
+                };
+                if args.is_empty() {
+                    name
+                } else {
+                    name.append(wrap_args(args.iter().map(|arg| {
+                        match &arg.label {
+                            Some(s) => s.clone().to_doc().append(": "),
+                            None => nil(),
+                        }
+                        .append(&arg.value)
+                    })))
+                }
+            }
+        }
+    }
+}
+
+/// Binary operators must be wrapped in braces to be indexed into or have a
+/// field accessed.
+///
+fn clause_guard_container(guard: &UntypedClauseGuard) -> Document {
+    match guard {
+        ClauseGuard::Var { .. }
+        | ClauseGuard::Int { .. }
+        | ClauseGuard::Float { .. }
+        | ClauseGuard::String { .. }
+        | ClauseGuard::Tuple { .. }
+        | ClauseGuard::TupleIndex { .. }
+        | ClauseGuard::FieldAccess { .. }
+        | ClauseGuard::Constructor { .. } => guard.to_doc(),
+
+        _ => guard.to_doc().surround("{", "}"),
+    }
+}
+
+fn starts_with_negate(expr: &UntypedExpr) -> bool {
+    match expr {
+        UntypedExpr::NegateInt { .. } | UntypedExpr::NegateFloat { .. } => true,
+        UntypedExpr::BinOp { left, .. } | UntypedExpr::Pipe { left, .. } => {
+            starts_with_negate(left)
         }
+        _ => false,
+    }
+}
+
+fn error_expr(keyword: &str, message: &Option<String>) -> Document {
+    match message {
+        None => keyword.to_doc(),
+        Some(message) => keyword
+            .to_doc()
+            .append(message.clone().to_doc().surround("(\"", "\")")),
     }
 }
 
@@ -1049,19 +1447,41 @@ fn categorise_list_pattern(expr: &UntypedPattern) -> ListType<&UntypedPattern, &
 }
 
 pub fn wrap_args<I>(args: I) -> Document
+where
+    I: Iterator<Item = Document>,
+{
+    wrap_args_commented(args, None)
+}
+
+/// Wrap arguments in parens, placing any comments that trail the final
+/// argument on their own lines before the closing paren.
+///
+fn wrap_args_commented<I>(args: I, comments: Option<Document>) -> Document
 where
     I: Iterator<Item = Document>,
 {
     let mut args = args.peekable();
-    if let None = args.peek() {
-        return "()".to_doc();
+    let has_args = args.peek().is_some();
+    let doc = break_("(", "(").append(concat(args.intersperse(delim(","))));
+    match comments {
+        None if !has_args => "()".to_doc(),
+
+        None => doc.nest(INDENT).append(break_(",", "")).append(")").group(),
+
+        Some(comments) if !has_args => "("
+            .to_doc()
+            .append(comments.nest(INDENT))
+            .append(line())
+            .append(")"),
+
+        Some(comments) => doc
+            .append(",")
+            .append(comments)
+            .nest(INDENT)
+            .append(line())
+            .append(")")
+            .group(),
     }
-    break_("(", "(")
-        .append(concat(args.intersperse(delim(","))))
-        .nest(INDENT)
-        .append(break_(",", ""))
-        .append(")")
-        .group()
 }
 
 pub fn wrap_args_with_spread<I>(args: I) -> Document
@@ -1096,20 +1516,22 @@ where
     (elems, tail)
 }
 
-fn list(elems: Document, tail: Option<Document>) -> Document {
+fn list(elems: Document, tail: Option<Document>, comments: Option<Document>) -> Document {
     let doc = break_("[", "[").append(elems);
 
-    match tail {
-        None => doc.nest(INDENT).append(break_(",", "")),
+    match (tail, comments) {
+        (None, None) => doc.nest(INDENT).append(break_(",", "")),
+
+        (None, Some(comments)) => doc.append(",").append(comments).nest(INDENT).append(line()),
 
         // Don't print tail if it is a discard
-        Some(Document::Text(t)) if t == "_".to_string() => doc
+        (Some(Document::Text(t)), _) if t == "_".to_string() => doc
             .append(break_(",", ", "))
             .append("..")
             .nest(INDENT)
             .append(break_("", "")),
 
-        Some(final_tail) => doc
+        (Some(final_tail), _) => doc
             .append(break_(",", ", "))
             .append("..")
             .append(final_tail)
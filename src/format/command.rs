@@ -1,6 +1,7 @@
 use crate::error::{Error, FileIOAction, FileKind, StandardIOAction};
-use std::fs::File;
-use std::io::{Read, Write};
+use crate::project::FormatConfig;
+use std::io::Read;
+use std::ops::Range;
 use std::path::PathBuf;
 use std::str::FromStr;
 
@@ -11,23 +12,130 @@ pub struct Formatted {
     pub output: String,
 }
 
-pub fn run(stdin: bool, check: bool, files: Vec<String>) -> Result<(), Error> {
+impl Formatted {
+    pub fn is_changed(&self) -> bool {
+        self.input != self.output
+    }
+
+    /// A unified diff of the input and the formatted output.
+    ///
+    pub fn diff(&self) -> String {
+        let path = self.path.to_string_lossy();
+        similar::TextDiff::from_lines(&self.input, &self.output)
+            .unified_diff()
+            .header(&path, &path)
+            .to_string()
+    }
+}
+
+pub fn run(
+    stdin: bool,
+    check: bool,
+    diff: bool,
+    range: Option<Range<usize>>,
+    files: Vec<String>,
+) -> Result<(), Error> {
+    // The offsets of a range are only meaningful within one file
+    let single_file = files.len() == 1 && PathBuf::from(&files[0]).is_file();
+    if range.is_some() && !stdin && !single_file {
+        return Err(Error::FormatRangeWithoutSingleFile);
+    }
+
+    let config = read_config()?;
+
     if stdin {
-        format_stdin(check)
+        return format_stdin(check, diff, range.as_ref(), &config);
+    }
+
+    let files = if files.is_empty() {
+        project_paths()?
     } else {
-        let formatted = read_and_format_paths(files)?;
-        if check {
-            check_formatting(formatted)
-        } else {
-            write_formatted(formatted)
-        }
+        files
+    };
+
+    let formatted = read_and_format_paths(files, range.as_ref(), &config)?;
+    if diff {
+        print_diffs(&formatted);
+    }
+    if check {
+        check_formatting(formatted)
+    } else if diff {
+        Ok(())
+    } else {
+        write_formatted(formatted)
+    }
+}
+
+/// Parse a byte range given as `start:end`.
+///
+pub fn parse_range(s: &str) -> Result<Range<usize>, String> {
+    let mut parts = s.splitn(2, ':');
+    let mut part = || {
+        parts
+            .next()
+            .and_then(|p| p.trim().parse::<usize>().ok())
+            .ok_or_else(|| format!("`{}` is not a range of the form start:end", s))
+    };
+    let start = part()?;
+    let end = part()?;
+    if start > end {
+        return Err(format!("the start of the range `{}` is after its end", s));
+    }
+    Ok(start..end)
+}
+
+/// The formatter config from the gleam.toml in the current directory, if
+/// there is one.
+///
+fn read_config() -> Result<FormatConfig, Error> {
+    let path = PathBuf::from("gleam.toml");
+    if !path.is_file() {
+        return Ok(FormatConfig::default());
+    }
+
+    let src = std::fs::read_to_string(&path).map_err(|e| Error::FileIO {
+        action: FileIOAction::Read,
+        kind: FileKind::File,
+        path: path.clone(),
+        err: Some(e.to_string()),
+    })?;
+    let (config, _warnings) = crate::project::parse_config(path, src)?;
+    Ok(config.format)
+}
+
+/// The source directories of the project in the current directory, used
+/// when no files are given to format.
+///
+fn project_paths() -> Result<Vec<String>, Error> {
+    let config_path = PathBuf::from("gleam.toml");
+    if !config_path.is_file() {
+        return Err(Error::FileIO {
+            action: FileIOAction::Open,
+            kind: FileKind::File,
+            path: config_path,
+            err: Some(
+                "Files to format must be given when not run from the root of a project".to_string(),
+            ),
+        });
+    }
+
+    Ok(["src", "test"]
+        .iter()
+        .filter(|dir| PathBuf::from(dir).is_dir())
+        .map(|dir| dir.to_string())
+        .collect())
+}
+
+fn print_diffs(formatted_files: &[Formatted]) {
+    for formatted in formatted_files.iter().filter(|f| f.is_changed()) {
+        print!("{}", formatted.diff());
     }
 }
 
 fn check_formatting(formatted_files: Vec<Formatted>) -> Result<(), Error> {
     let problem_files: Vec<_> = formatted_files
         .into_iter()
-        .filter(|formatted| formatted.input != formatted.output)
+        .filter(|formatted| formatted.is_changed())
         .collect();
 
     if problem_files.is_empty() {
@@ -37,29 +145,43 @@ fn check_formatting(formatted_files: Vec<Formatted>) -> Result<(), Error> {
     }
 }
 
-fn write_formatted(formatted_files: Vec<Formatted>) -> Result<(), Error> {
-    for formatted in formatted_files {
+/// Write the formatted source over each file that has changed. The output
+/// is written to a temporary file which is then renamed over the original,
+/// so a file is never left partially written.
+///
+pub(crate) fn write_formatted(formatted_files: Vec<Formatted>) -> Result<(), Error> {
+    for formatted in formatted_files.into_iter().filter(|f| f.is_changed()) {
         let path = formatted.path;
-        let mut f = File::open(&path).map_err(|e| Error::FileIO {
-            action: FileIOAction::Create,
+        let mut tmp_name = path.file_name().unwrap_or_default().to_os_string();
+        tmp_name.push(".tmp");
+        let tmp_path = path.with_file_name(tmp_name);
+
+        std::fs::write(&tmp_path, formatted.output.as_bytes()).map_err(|e| Error::FileIO {
+            action: FileIOAction::WriteTo,
             kind: FileKind::File,
-            path: path.clone(),
+            path: tmp_path.clone(),
             err: Some(e.to_string()),
         })?;
 
-        f.write_all(formatted.output.as_bytes())
-            .map_err(|e| Error::FileIO {
-                action: FileIOAction::WriteTo,
+        std::fs::rename(&tmp_path, &path).map_err(|e| {
+            let _ = std::fs::remove_file(&tmp_path);
+            Error::FileIO {
+                action: FileIOAction::Rename,
                 kind: FileKind::File,
-                path: path.clone(),
+                path: tmp_path.clone(),
                 err: Some(e.to_string()),
-            })?;
+            }
+        })?;
     }
 
     Ok(())
 }
 
-pub fn read_and_format_paths(files: Vec<String>) -> Result<Vec<Formatted>, Error> {
+pub fn read_and_format_paths(
+    files: Vec<String>,
+    range: Option<&Range<usize>>,
+    config: &FormatConfig,
+) -> Result<Vec<Formatted>, Error> {
     let mut formatted_files = Vec::with_capacity(files.len());
 
     for file_path in files {
@@ -72,17 +194,21 @@ pub fn read_and_format_paths(files: Vec<String>) -> Result<Vec<Formatted>, Error
 
         if path.is_dir() {
             for path in crate::project::gleam_files(&path).into_iter() {
-                formatted_files.push(format_file(path)?);
+                formatted_files.push(format_file(path, range, config)?);
             }
         } else {
-            formatted_files.push(format_file(path)?);
+            formatted_files.push(format_file(path, range, config)?);
         }
     }
 
     Ok(formatted_files)
 }
 
-fn format_file(path: PathBuf) -> Result<Formatted, Error> {
+fn format_file(
+    path: PathBuf,
+    range: Option<&Range<usize>>,
+    config: &FormatConfig,
+) -> Result<Formatted, Error> {
     let src = std::fs::read_to_string(&path).map_err(|e| Error::FileIO {
         action: FileIOAction::Read,
         kind: FileKind::File,
@@ -90,12 +216,36 @@ fn format_file(path: PathBuf) -> Result<Formatted, Error> {
         err: Some(e.to_string()),
     })?;
 
-    let formatted = crate::format::pretty(src.as_ref()).map_err(|error| Error::Parse {
+    format_src(path, src, range, config)
+}
+
+/// Format source code, or only the statements within a range of it, refusing
+/// to return output that has lost any of the comments in the original source.
+///
+fn format_src(
+    path: PathBuf,
+    src: String,
+    range: Option<&Range<usize>>,
+    config: &FormatConfig,
+) -> Result<Formatted, Error> {
+    let formatted = match range {
+        Some(range) => crate::format::pretty_range(src.as_ref(), range, config),
+        None => crate::format::pretty_with_config(src.as_ref(), config),
+    }
+    .map_err(|error| Error::Parse {
         path: path.clone(),
         src: src.clone(),
         error,
     })?;
 
+    if let Some(location) = crate::format::lost_comment(&src, &formatted) {
+        return Err(Error::FormatLostComment {
+            path,
+            src,
+            location,
+        });
+    }
+
     Ok(Formatted {
         path,
         input: src,
@@ -103,7 +253,12 @@ fn format_file(path: PathBuf) -> Result<Formatted, Error> {
     })
 }
 
-pub fn format_stdin(_check: bool) -> Result<(), Error> {
+pub fn format_stdin(
+    check: bool,
+    diff: bool,
+    range: Option<&Range<usize>>,
+    config: &FormatConfig,
+) -> Result<(), Error> {
     let mut src = String::new();
     std::io::stdin()
         .read_to_string(&mut src)
@@ -112,12 +267,84 @@ pub fn format_stdin(_check: bool) -> Result<(), Error> {
             err: Some(e.kind()),
         })?;
 
-    let formatted = crate::format::pretty(src.as_ref()).map_err(|error| Error::Parse {
-        path: PathBuf::from("<standard input>"),
-        error,
-        src,
-    })?;
+    let formatted = format_src(PathBuf::from("<standard input>"), src, range, config)?;
 
-    print!("{}", formatted);
-    Ok(())
+    if diff {
+        print_diffs(std::slice::from_ref(&formatted));
+    } else if !check {
+        print!("{}", formatted.output);
+    }
+
+    if check {
+        check_formatting(vec![formatted])
+    } else {
+        Ok(())
+    }
+}
+
+#[test]
+fn write_formatted_test() {
+    let dir = std::env::temp_dir().join("gleam_write_formatted_test");
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("one.gleam");
+    std::fs::write(&path, "fn main() {   1 }\n").unwrap();
+
+    let formatted = read_and_format_paths(
+        vec![dir.to_string_lossy().to_string()],
+        None,
+        &FormatConfig::default(),
+    )
+    .unwrap();
+    assert_eq!(
+        "--- PATH\n+++ PATH\n@@ -1 +1,3 @@\n-fn main() {   1 }\n+fn main() {\n+  1\n+}\n",
+        formatted[0]
+            .diff()
+            .replace(path.to_string_lossy().as_ref(), "PATH")
+    );
+
+    write_formatted(formatted).unwrap();
+    assert_eq!(
+        "fn main() {\n  1\n}\n",
+        std::fs::read_to_string(&path).unwrap()
+    );
+    let files: Vec<_> = std::fs::read_dir(&dir).unwrap().collect();
+    assert_eq!(1, files.len());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn run_range_test() {
+    let dir = std::env::temp_dir().join("gleam_run_range_test");
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    let one = dir.join("one.gleam").to_string_lossy().to_string();
+    let two = dir.join("two.gleam").to_string_lossy().to_string();
+    std::fs::write(&one, "fn main() {   1 }\n").unwrap();
+    std::fs::write(&two, "fn main() {   2 }\n").unwrap();
+
+    // A range can only be used with exactly one file
+    let run_range = |files: Vec<String>| run(false, true, false, Some(0..5), files);
+    assert_eq!(Err(Error::FormatRangeWithoutSingleFile), run_range(vec![]));
+    assert_eq!(
+        Err(Error::FormatRangeWithoutSingleFile),
+        run_range(vec![dir.to_string_lossy().to_string()])
+    );
+    assert_eq!(
+        Err(Error::FormatRangeWithoutSingleFile),
+        run_range(vec![one.clone(), two])
+    );
+    assert!(matches!(run_range(vec![one]), Err(Error::Format { .. })));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn parse_range_test() {
+    assert_eq!(Ok(1..5), parse_range("1:5"));
+    assert_eq!(Ok(3..3), parse_range("3:3"));
+    assert!(parse_range("5:1").is_err());
+    assert!(parse_range("5").is_err());
+    assert!(parse_range("a:b").is_err());
 }
@@ -6,7 +6,10 @@ fn module_test() {
         ($src:expr $(,)?) => {
             println!("\n\n\n{}", $src);
             let src = $src.to_string();
-            assert_eq!(src, pretty($src).unwrap());
+            assert_eq!(
+                src,
+                pretty_with_config($src, &FormatConfig::default()).unwrap()
+            );
         };
     }
 
@@ -747,6 +750,83 @@ pub external type Four
 "#
     );
 
+    assert_format!(
+        r#"fn main() {
+  "a" <> b <> "c"
+}
+"#
+    );
+
+    assert_format!(
+        r#"fn main() {
+  "Hello, " <> name == greeting
+}
+"#
+    );
+
+    //
+    // Unary operators
+    //
+
+    assert_format!(
+        r#"fn main() {
+  -x
+}
+"#
+    );
+
+    assert_format!(
+        r#"fn main() {
+  -.x
+}
+"#
+    );
+
+    assert_format!(
+        r#"fn main() {
+  !x
+}
+"#
+    );
+
+    assert_format!(
+        r#"fn main() {
+  -{ x + y } * -z
+}
+"#
+    );
+
+    assert_format!(
+        r#"fn main() {
+  !{ x && y } || !z
+}
+"#
+    );
+
+    assert_format!(
+        r#"fn main() {
+  x - -y
+}
+"#
+    );
+
+    assert_format!(
+        r#"fn main() {
+  x
+  { -y }
+  { -.z +. 1.0 }
+}
+"#
+    );
+
+    assert_format!(
+        r#"fn main() {
+  let x = 1
+  { -x }
+}
+"#
+    );
+
     //
     // Int
     //
@@ -772,6 +852,23 @@ pub external type Four
 "#
     );
 
+    assert_format!(
+        r#"fn main() {
+  0xFF + 0o17 + 0b1010 + 1_000_000 + -0xf
+}
+"#
+    );
+
+    assert_format!(
+        r#"fn main() {
+  case x {
+    0x1F -> 1
+    _ if x > 1_000 -> 2
+  }
+}
+"#
+    );
+
     //
     // Float
     //
@@ -797,6 +894,13 @@ pub external type Four
 "#
     );
 
+    assert_format!(
+        r#"fn main() {
+  1.5e-3 +. 1_000.000_1 +. -2.0E10
+}
+"#
+    );
+
     //
     // String
     //
@@ -1140,6 +1244,22 @@ World"
 "#
     );
 
+    assert_format!(
+        r#"fn main() {
+  let x: Int = 1
+  Nil
+}
+"#
+    );
+
+    assert_format!(
+        r#"fn main() {
+  assert Ok(x): Result(List(a), String) = y
+  Nil
+}
+"#
+    );
+
     assert_format!(
         r#"fn main() {
   let x = {
@@ -1676,6 +1796,60 @@ World"
 "#
     );
 
+    assert_format!(
+        r#"fn main() {
+  case 1 {
+    _ if x == "ok" -> Nil
+  }
+}
+"#
+    );
+
+    assert_format!(
+        r#"fn main() {
+  case 1 {
+    _ if x == tuple(1, 2.0) || x.0 == 3 -> Nil
+  }
+}
+"#
+    );
+
+    assert_format!(
+        r#"fn main() {
+  case 1 {
+    _ if x.name == y.name -> Nil
+  }
+}
+"#
+    );
+
+    assert_format!(
+        r#"fn main() {
+  case 1 {
+    _ if {x == y}.0 -> Nil
+  }
+}
+"#
+    );
+
+    assert_format!(
+        r#"fn main() {
+  case 1 {
+    _ if x == Ok(Nil) || x == Person(name: "Bob", age: 30) -> Nil
+  }
+}
+"#
+    );
+
+    assert_format!(
+        r#"fn main() {
+  case 1 {
+    _ if x == option.None || x == option.Some(1) -> Nil
+  }
+}
+"#
+    );
+
     //
     // FieldAccess
     //
@@ -1782,6 +1956,23 @@ World"
 "
     );
 
+    assert_format!(
+        r#"fn main() {
+  todo("Implement the main function")
+}
+"#
+    );
+
+    assert_format!(
+        r#"fn main(x) {
+  case x {
+    1 -> panic
+    _ -> panic("Unexpected value")
+  }
+}
+"#
+    );
+
     //
     // Doc comments
     //
@@ -1852,6 +2043,38 @@ type Whatever {
 "#
     );
 
+    //
+    // Deprecation attributes
+    //
+    assert_format!(
+        r#"@deprecated("Use new_main")
+pub fn main() {
+  Nil
+}
+"#
+    );
+
+    assert_format!(
+        r#"/// one
+@deprecated("Use new_whatever")
+external fn whatever() -> Nil =
+  "" ""
+"#
+    );
+
+    assert_format!(
+        r#"/// one
+@deprecated("Use Thing")
+pub type Whatever {
+  Whatever
+}
+
+@deprecated("Use Int")
+type Number =
+  Int
+"#
+    );
+
     //
     // Comments
     //
@@ -2113,6 +2336,112 @@ type Whatever {
 "
     );
 
+    //
+    // Comments at the end of expressions
+    //
+
+    assert_format!(
+        "fn main() {
+  x
+  // Hello world
+}
+"
+    );
+
+    assert_format!(
+        "fn main() {
+  foo(
+    1,
+    2,
+    // Hello world
+  )
+}
+"
+    );
+
+    assert_format!(
+        "fn main() {
+  foo(
+    // Hello world
+  )
+}
+"
+    );
+
+    assert_format!(
+        "fn main() {
+  tuple(
+    1,
+    // Hello world
+  )
+}
+"
+    );
+
+    assert_format!(
+        "fn main() {
+  [
+    1,
+    2,
+    // Hello world
+  ]
+}
+"
+    );
+
+    assert_format!(
+        "fn main() {
+  [
+    // Hello world
+  ]
+}
+"
+    );
+
+    assert_format!(
+        "fn main() {
+  let x = [
+    // Hello world
+    1,
+    2,
+  ]
+  x
+}
+"
+    );
+
+    assert_format!(
+        "fn main() {
+  case x {
+    1 -> 1
+    // Hello world
+  }
+}
+"
+    );
+
+    assert_format!(
+        "fn main() {
+  fn(x) {
+    x
+    // Hello world
+  }
+}
+"
+    );
+
+    assert_format!(
+        "fn main() {
+  x
+  // Hello world
+}
+
+fn other() {
+  x
+}
+"
+    );
+
     //
     // Commented function arguments
     //
@@ -2448,11 +2777,177 @@ type X {
     );
 }
 
+#[test]
+fn config_test() {
+    let config = FormatConfig {
+        line_width: 20,
+        sort_imports: true,
+    };
+    macro_rules! assert_format_config {
+        ($src:expr, $output:expr $(,)?) => {
+            assert_eq!($output, pretty_with_config($src, &config).unwrap());
+        };
+    }
+
+    assert_format_config!(
+        "fn main() {
+  foo(one, two, three)
+}
+",
+        "fn main() {
+  foo(
+    one,
+    two,
+    three,
+  )
+}
+",
+    );
+
+    assert_format_config!(
+        "import one/two
+import one.{b, B, a}
+import one/three as four
+",
+        "import one.{B, a, b}
+import one/three as four
+import one/two
+",
+    );
+
+    assert_format_config!(
+        "import zero
+// The first
+import one
+
+import two
+import one/two
+",
+        "// The first
+import one
+import zero
+
+import one/two
+import two
+",
+    );
+
+    // Imports are not sorted by default
+    assert_eq!(
+        "import two.{b, a}
+import one
+",
+        pretty_with_config(
+            "import two.{b, a}\n\nimport one\n",
+            &FormatConfig::default()
+        )
+        .unwrap()
+    );
+}
+
+#[test]
+fn pretty_range_test() {
+    macro_rules! assert_format_range {
+        ($src:expr, $range:expr, $output:expr $(,)?) => {
+            assert_eq!(
+                $output,
+                pretty_range($src, &$range, &FormatConfig::default()).unwrap()
+            );
+        };
+    }
+
+    let src = "import   one
+
+// One
+fn one() {   1 }
+
+/// Two
+@deprecated(\"no\")
+fn two() {   2 }  // Two
+";
+
+    assert_format_range!(
+        src,
+        0..0,
+        "import one
+
+// One
+fn one() {   1 }
+
+/// Two
+@deprecated(\"no\")
+fn two() {   2 }  // Two
+"
+    );
+
+    assert_format_range!(
+        src,
+        30..30,
+        "import   one
+
+// One
+fn one() {
+  1
+}
+
+/// Two
+@deprecated(\"no\")
+fn two() {   2 }  // Two
+"
+    );
+
+    assert_format_range!(
+        src,
+        30..60,
+        "import   one
+
+// One
+fn one() {
+  1
+}
+
+/// Two
+@deprecated(\"no\")
+fn two() {
+  2
+}  // Two
+"
+    );
+
+    assert_format_range!(src, 14..15, src);
+    assert_format_range!("", 0..10, "");
+}
+
+#[test]
+fn lost_comment_test() {
+    assert_eq!(
+        None,
+        lost_comment(
+            "// one\n/// two\nfn main() { 1 }\n",
+            "// one\n/// two\nfn main() {\n  1\n}\n"
+        )
+    );
+    assert_eq!(
+        Some(SrcSpan { start: 7, end: 13 }),
+        lost_comment(
+            "// one\n// two\nfn main() { 1 }\n",
+            "// one\nfn main() {\n  1\n}\n"
+        )
+    );
+    assert_eq!(
+        Some(SrcSpan { start: 7, end: 13 }),
+        lost_comment("// one\n// one\n", "// one\n")
+    );
+}
+
 #[test]
 fn module_rewrites_test() {
     macro_rules! assert_format_rewrite {
         ($src:expr, $output:expr  $(,)?) => {
-            assert_eq!(pretty($src).unwrap(), $output);
+            assert_eq!(
+                pretty_with_config($src, &FormatConfig::default()).unwrap(),
+                $output
+            );
         };
     }
 
@@ -0,0 +1,43 @@
+/// Maps byte indexes in a source file to the line they are on, for use when
+/// something has a `SrcSpan` but needs to refer to a line (eg. links to the
+/// source code from the documentation).
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct LineNumbers {
+    line_starts: Vec<usize>,
+}
+
+impl LineNumbers {
+    pub fn new(src: &str) -> Self {
+        Self {
+            line_starts: std::iter::once(0)
+                .chain(src.match_indices('\n').map(|(i, _)| i + 1))
+                .collect(),
+        }
+    }
+
+    /// Get the 1-indexed line number for a byte index
+    ///
+    pub fn line_number(&self, byte_index: usize) -> usize {
+        self.line_starts
+            .binary_search(&byte_index)
+            .unwrap_or_else(|next_line| next_line - 1)
+            + 1
+    }
+}
+
+#[test]
+fn line_number_test() {
+    let lines = LineNumbers::new("");
+    assert_eq!(1, lines.line_number(0));
+
+    let lines = LineNumbers::new("one\ntwo\n\nfour");
+    assert_eq!(1, lines.line_number(0));
+    assert_eq!(1, lines.line_number(2));
+    assert_eq!(1, lines.line_number(3));
+    assert_eq!(2, lines.line_number(4));
+    assert_eq!(2, lines.line_number(7));
+    assert_eq!(3, lines.line_number(8));
+    assert_eq!(4, lines.line_number(9));
+    assert_eq!(4, lines.line_number(12));
+}
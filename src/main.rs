@@ -4,9 +4,13 @@ mod ast;
 mod diagnostic;
 mod doc;
 mod erl;
+mod erlc;
 mod error;
+mod fix;
 mod format;
+mod line_numbers;
 mod new;
+mod package_interface;
 mod parser;
 mod pretty;
 mod project;
@@ -36,11 +40,13 @@ extern crate lazy_static;
 
 use crate::{
     error::Error,
+    package_interface::PackageInterface,
     project::{ModuleOrigin, OutputFile, ProjectConfig},
+    warning::Warning,
 };
 use std::fs::File;
 use std::io::{Read, Write};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use structopt::clap::AppSettings;
 use structopt::StructOpt;
 use strum::VariantNames;
@@ -50,12 +56,45 @@ const VERSION: &str = env!("CARGO_PKG_VERSION");
 #[derive(StructOpt, Debug)]
 #[structopt(global_settings = &[AppSettings::ColoredHelp, AppSettings::VersionlessSubcommands])]
 enum Command {
+    #[structopt(
+        name = "api-diff",
+        about = "Compare the public API of two versions of a package"
+    )]
+    ApiDiff {
+        #[structopt(
+            help = "the old version, as a project directory or a file written by export package-interface"
+        )]
+        old: String,
+        #[structopt(
+            help = "the new version, as a project directory or a file written by export package-interface",
+            default_value = "."
+        )]
+        new: String,
+    },
+
     #[structopt(name = "build", about = "Compile a project")]
     Build {
         #[structopt(help = "location of the project root", default_value = ".")]
         path: String,
         #[structopt(help = "generate docs for this package as well", long)]
         doc: bool,
+        #[structopt(help = "the build profile to use", long, default_value = "dev")]
+        profile: String,
+    },
+
+    #[structopt(
+        name = "deps",
+        about = "Work with the modules of a project and its dependencies"
+    )]
+    Deps(Deps),
+
+    #[structopt(name = "export", about = "Export information about a project")]
+    Export(Export),
+
+    #[structopt(name = "fix", about = "Rewrite deprecated syntax in a project")]
+    Fix {
+        #[structopt(help = "location of the project root", default_value = ".")]
+        path: String,
     },
 
     #[structopt(name = "new", about = "Create a new project")]
@@ -80,7 +119,10 @@ enum Command {
 
     #[structopt(name = "format", about = "Format source code")]
     Format {
-        #[structopt(help = "files to format", conflicts_with = "stdin")]
+        #[structopt(
+            help = "files to format, defaulting to the src and test directories of the project",
+            conflicts_with = "stdin"
+        )]
         files: Vec<String>,
 
         #[structopt(
@@ -95,18 +137,75 @@ enum Command {
             long = "check"
         )]
         check: bool,
+
+        #[structopt(
+            help = "print a diff of the changes formatting would make without changing the inputs",
+            long = "diff"
+        )]
+        diff: bool,
+
+        #[structopt(
+            help = "only format the top level definitions overlapping a byte range, given as start:end, in a single file or standard input",
+            long = "range",
+            parse(try_from_str = format::command::parse_range)
+        )]
+        range: Option<std::ops::Range<usize>>,
+    },
+}
+
+#[derive(StructOpt, Debug)]
+enum Deps {
+    #[structopt(name = "graph", about = "Print the graph of imports between modules")]
+    Graph {
+        #[structopt(help = "location of the project root", default_value = ".")]
+        path: String,
+
+        #[structopt(
+            long = "format",
+            possible_values = &project::import_graph::GraphFormat::VARIANTS,
+            case_insensitive = true,
+            default_value = "dot"
+        )]
+        format: project::import_graph::GraphFormat,
+    },
+}
+
+#[derive(StructOpt, Debug)]
+enum Export {
+    #[structopt(
+        name = "package-interface",
+        about = "Write the public API of the package as JSON"
+    )]
+    PackageInterface {
+        #[structopt(help = "location of the project root", default_value = ".")]
+        path: String,
+
+        #[structopt(help = "the file to write the interface to", long = "out")]
+        out: String,
     },
 }
 
 fn main() {
     let result = match Command::from_args() {
-        Command::Build { path, doc } => command_build(path, doc),
+        Command::ApiDiff { old, new } => command_api_diff(old, new),
+
+        Command::Build { path, doc, profile } => command_build(path, doc, profile),
 
         Command::Format {
             stdin,
             files,
             check,
-        } => crate::format::command::run(stdin, check, files),
+            diff,
+            range,
+        } => crate::format::command::run(stdin, check, diff, range, files),
+
+        Command::Deps(Deps::Graph { path, format }) => command_deps_graph(path, format),
+
+        Command::Export(Export::PackageInterface { path, out }) => {
+            command_export_package_interface(path, out)
+        }
+
+        Command::Fix { path } => command_fix(path),
 
         Command::New {
             name,
@@ -122,53 +221,65 @@ fn main() {
     }
 }
 
-fn command_build(root: String, write_docs: bool) -> Result<(), Error> {
-    let mut srcs = vec![];
-
+fn command_build(root: String, write_docs: bool, profile: String) -> Result<(), Error> {
     // Read gleam.toml
-    let project_config = read_project_config(&root)?;
+    let (project_config, config_warnings) = read_project_config(&root)?;
+    let profile = project_config.profile(&profile)?;
 
     let root_path = PathBuf::from(&root);
-    let lib_dir = root_path.join("_build").join("default").join("lib");
+    let lib_dir = root_path.join(&profile.build_dir).join("lib");
     let checkouts_dir = root_path.join("_checkouts");
 
-    for project_dir in [lib_dir, checkouts_dir]
+    let (srcs, source_warnings) = collect_project_source(&root_path, &project_config, &profile)?;
+    let analysed = crate::project::analysed(srcs, &project_config.forbidden_imports)?;
+
+    // Print warnings
+    let warnings: Vec<_> = config_warnings
         .iter()
-        .filter_map(|d| std::fs::read_dir(d).ok())
-        .flat_map(|d| d.filter_map(Result::ok))
-        .map(|d| d.path())
-        .filter(|p| {
-            p.file_name().and_then(|os_string| os_string.to_str()) != Some(&project_config.name)
-        })
-    {
-        crate::project::collect_source(
-            project_dir.join("src"),
-            ModuleOrigin::Dependency,
-            &mut srcs,
-        )?;
+        .chain(source_warnings.iter())
+        .chain(analysed.iter().flat_map(|a| a.warnings.iter()))
+        .collect();
+    for w in warnings.iter() {
+        w.pretty_print()
+    }
+    if profile.warnings_as_errors && !warnings.is_empty() {
+        return Err(Error::WarningsAsErrors {
+            profile: profile.name,
+            count: warnings.len(),
+        });
     }
 
-    // Collect source code from top level project
-    crate::project::collect_source(root_path.join("src"), ModuleOrigin::Src, &mut srcs)?;
-    crate::project::collect_source(root_path.join("test"), ModuleOrigin::Test, &mut srcs)?;
-
-    let analysed = crate::project::analysed(srcs)?;
+    // Hand-written Erlang modules are compiled with the generated ones
+    let erlang_files: Vec<_> = crate::project::erlang_files(&root_path.join("src")).collect();
 
     // Generate outputs (Erlang code, html documentation, etc)
     let mut output_files = vec![];
+    let gen_dir = root_path.join(&profile.gen_dir);
     if write_docs {
-        let dir = root_path.join("docs");
+        let dir = root_path.join(&profile.docs_dir);
+        let path_dependencies = if project_config.docs.include_path_dependencies {
+            read_path_dependencies(&checkouts_dir)
+        } else {
+            vec![]
+        };
         crate::doc::generate_html(
             &project_config,
+            path_dependencies.as_slice(),
             analysed.as_slice(),
             &mut output_files,
             &dir,
         );
         delete_dir(&dir)?;
     } else {
-        let dir = root_path.join("gen");
-        crate::project::generate_erlang(analysed.as_slice(), &mut output_files);
-        delete_dir(&dir)?;
+        crate::project::generate_erlang(analysed.as_slice(), &profile.gen_dir, &mut output_files);
+        crate::project::generate_app_file(
+            &project_config,
+            analysed.as_slice(),
+            erlang_files.as_slice(),
+            &gen_dir.join("ebin"),
+            &mut output_files,
+        );
+        delete_dir(&gen_dir)?;
     }
 
     // Delete the gen directory before generating the newly compiled files
@@ -176,10 +287,22 @@ fn command_build(root: String, write_docs: bool) -> Result<(), Error> {
         write_file(file)?;
     }
 
-    // Print warnings
-    for a in analysed.iter() {
-        for w in a.warnings.iter() {
-            w.pretty_print()
+    // Compile the generated Erlang to BEAM
+    if !write_docs {
+        let app_file = format!("{}.app", project_config.name);
+        let ebin = lib_dir.join(&project_config.name).join("ebin");
+        let modules: Vec<_> = analysed
+            .iter()
+            .filter(|m| m.origin != ModuleOrigin::Dependency)
+            .collect();
+        delete_dir(&ebin)?;
+        match crate::erlc::compile(modules.as_slice(), erlang_files.as_slice(), &profile, &ebin)? {
+            crate::erlc::Outcome::Compiled => {
+                copy_file(&gen_dir.join("ebin").join(&app_file), &ebin.join(&app_file))?;
+            }
+            crate::erlc::Outcome::ErlcNotFound => {
+                println!("erlc not found, skipping compilation of Erlang to BEAM")
+            }
         }
     }
 
@@ -188,6 +311,125 @@ fn command_build(root: String, write_docs: bool) -> Result<(), Error> {
     Ok(())
 }
 
+/// Collect the source of the project and its dependencies, along with
+/// warnings for any dependency files that were skipped. Test modules are
+/// included if the build profile includes them.
+///
+fn collect_project_source(
+    root_path: &Path,
+    project_config: &ProjectConfig,
+    profile: &crate::project::BuildProfile,
+) -> Result<(Vec<crate::project::Input>, Vec<Warning>), Error> {
+    let mut srcs = vec![];
+    let mut warnings = vec![];
+    let lib_dir = root_path.join(&profile.build_dir).join("lib");
+    let checkouts_dir = root_path.join("_checkouts");
+
+    for project_dir in [lib_dir, checkouts_dir]
+        .iter()
+        .filter_map(|d| std::fs::read_dir(d).ok())
+        .flat_map(|d| d.filter_map(Result::ok))
+        .map(|d| d.path())
+        .filter(|p| {
+            p.file_name().and_then(|os_string| os_string.to_str()) != Some(&project_config.name)
+        })
+    {
+        crate::project::collect_source(
+            project_dir.join("src"),
+            ModuleOrigin::Dependency,
+            &mut srcs,
+            &mut warnings,
+        )?;
+    }
+
+    // Collect source code from top level project
+    crate::project::collect_source(
+        root_path.join("src"),
+        ModuleOrigin::Src,
+        &mut srcs,
+        &mut warnings,
+    )?;
+    if profile.include_tests {
+        crate::project::collect_source(
+            root_path.join("test"),
+            ModuleOrigin::Test,
+            &mut srcs,
+            &mut warnings,
+        )?;
+    }
+
+    Ok((srcs, warnings))
+}
+
+fn command_deps_graph(
+    root: String,
+    format: project::import_graph::GraphFormat,
+) -> Result<(), Error> {
+    let (project_config, _) = read_project_config(&root)?;
+    let profile = project_config.profile("dev")?;
+    let (srcs, _) = collect_project_source(&PathBuf::from(&root), &project_config, &profile)?;
+    let graph = crate::project::import_graph(srcs)?;
+    print!("{}", graph.render(&format));
+    Ok(())
+}
+
+fn command_export_package_interface(root: String, out: String) -> Result<(), Error> {
+    let interface = build_package_interface(&root)?;
+    write_file(OutputFile {
+        path: PathBuf::from(out),
+        text: interface.to_json(),
+    })
+}
+
+fn command_api_diff(old: String, new: String) -> Result<(), Error> {
+    let read = |path: &str| {
+        if PathBuf::from(path).is_dir() {
+            build_package_interface(path)
+        } else {
+            PackageInterface::read(&PathBuf::from(path))
+        }
+    };
+    let old = read(&old)?;
+    let new = read(&new)?;
+    let changes = package_interface::diff::diff(&old, &new);
+    print!("{}", package_interface::diff::report(&changes));
+    Ok(())
+}
+
+/// The public API of the project, built without its tests.
+///
+fn build_package_interface(root: &str) -> Result<PackageInterface, Error> {
+    let (project_config, _) = read_project_config(root)?;
+    let profile = project_config.profile("prod")?;
+    let (srcs, _) = collect_project_source(&PathBuf::from(root), &project_config, &profile)?;
+    let analysed = crate::project::analysed(srcs, &project_config.forbidden_imports)?;
+    Ok(PackageInterface::new(&project_config, &analysed))
+}
+
+fn command_fix(root: String) -> Result<(), Error> {
+    let (project_config, _) = read_project_config(&root)?;
+    crate::fix::run(&root, &project_config.format)
+}
+
+/// Read the config of each Gleam project checked out locally in the given
+/// directory. Checkouts without a gleam.toml are not Gleam projects and are
+/// skipped.
+///
+fn read_path_dependencies(checkouts_dir: &PathBuf) -> Vec<crate::doc::PathDependency> {
+    let mut dependencies: Vec<_> = std::fs::read_dir(checkouts_dir)
+        .into_iter()
+        .flat_map(|d| d.filter_map(Result::ok))
+        .map(|d| d.path())
+        .filter(|p| p.join("gleam.toml").is_file())
+        .filter_map(|root| {
+            let (config, _) = read_project_config(root.to_str()?).ok()?;
+            Some(crate::doc::PathDependency { config, root })
+        })
+        .collect();
+    dependencies.sort_by(|a, b| a.config.name.cmp(&b.config.name));
+    dependencies
+}
+
 fn delete_dir(dir: &PathBuf) -> Result<(), Error> {
     if dir.exists() {
         std::fs::remove_dir_all(&dir).map_err(|e| Error::FileIO {
@@ -200,6 +442,16 @@ fn delete_dir(dir: &PathBuf) -> Result<(), Error> {
     Ok(())
 }
 
+fn copy_file(from: &PathBuf, to: &PathBuf) -> Result<(), Error> {
+    std::fs::copy(from, to).map_err(|e| Error::FileIO {
+        action: error::FileIOAction::WriteTo,
+        kind: error::FileKind::File,
+        path: to.clone(),
+        err: Some(e.to_string()),
+    })?;
+    Ok(())
+}
+
 pub fn write_file(file: OutputFile) -> Result<(), Error> {
     let OutputFile { path, text } = file;
 
@@ -233,7 +485,7 @@ pub fn write_file(file: OutputFile) -> Result<(), Error> {
     Ok(())
 }
 
-fn read_project_config(root: &str) -> Result<ProjectConfig, Error> {
+fn read_project_config(root: &str) -> Result<(ProjectConfig, Vec<Warning>), Error> {
     let config_path = PathBuf::from(root).join("gleam.toml");
 
     let mut file = File::open(&config_path).map_err(|e| Error::FileIO {
@@ -251,12 +503,5 @@ fn read_project_config(root: &str) -> Result<ProjectConfig, Error> {
         err: Some(e.to_string()),
     })?;
 
-    let project_config = toml::from_str(&toml).map_err(|e| Error::FileIO {
-        action: error::FileIOAction::Parse,
-        kind: error::FileKind::File,
-        path: config_path.clone(),
-        err: Some(e.to_string()),
-    })?;
-
-    Ok(project_config)
+    crate::project::parse_config(config_path, toml)
 }
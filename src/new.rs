@@ -6,7 +6,7 @@ use std::path::Path;
 use std::path::PathBuf;
 use strum_macros::{Display, EnumString, EnumVariantNames};
 
-#[derive(Debug, Serialize, Deserialize, Display, EnumString, EnumVariantNames)]
+#[derive(Debug, PartialEq, Serialize, Deserialize, Display, EnumString, EnumVariantNames)]
 #[strum(serialize_all = "kebab_case")]
 pub enum Template {
     Lib,
@@ -48,27 +48,23 @@ pub fn create(
     write(root_dir.join("LICENSE"), APACHE_2)?;
     write(root_dir.join(".gitignore"), GITIGNORE)?;
     write(root_dir.join("README.md"), &readme(&name, &description))?;
-    write(root_dir.join("gleam.toml"), &gleam_toml(&name))?;
+    write(
+        root_dir.join("gleam.toml"),
+        &gleam_toml(&name, &description, template == Template::App),
+    )?;
     write(test_dir.join(format!("{}_test.gleam", name)), &test(&name))?;
     write(src_dir.join(format!("{}.gleam", name)), &src(&name))?;
     write(workflows_dir.join("test.yml"), &github_ci(version))?;
 
+    // The .app file is generated by `gleam build` from gleam.toml
     match template {
         Template::Lib => {
             write(root_dir.join("rebar.config"), &rebar_config(""))?;
-            write(
-                src_dir.join(format!("{}.app.src", name)),
-                &app_src(&name, &description, false),
-            )?;
         }
 
         Template::App => {
             write(root_dir.join("rebar.config"), &app_rebar_config(&name))?;
             write(src_dir.join(format!("{}_app.erl", name)), &src_app(&name))?;
-            write(
-                src_dir.join(format!("{}.app.src", name)),
-                &app_src(&name, &description, true),
-            )?;
         }
     }
 
@@ -118,8 +114,25 @@ fn write(path: PathBuf, contents: &str) -> Result<(), Error> {
     Ok(())
 }
 
-fn gleam_toml(name: &str) -> String {
-    format!("name = \"{}\"\n", name)
+fn gleam_toml(name: &str, description: &str, is_application: bool) -> String {
+    let erlang = if is_application {
+        format!("\n[erlang]\napplication_start_module = \"{}_app\"\n", name)
+    } else {
+        "".to_string()
+    };
+    format!(
+        r#"name = "{}"
+version = "1.0.0"
+description = "{}"
+licences = ["Apache-2.0"]
+
+[dependencies]
+gleam_stdlib = "0.8.0"
+{}"#,
+        name,
+        description.replace('"', "\\\""),
+        erlang
+    )
 }
 
 fn readme(name: &str, description: &str) -> String {
@@ -187,33 +200,6 @@ jobs:
     )
 }
 
-fn app_src(name: &str, description: &str, is_application: bool) -> String {
-    let module = if is_application {
-        format!("\n  {{mod, {{{}_app, []}}}},", name)
-    } else {
-        "".to_string()
-    };
-    format!(
-        r#"{{application, {},
- [{{description, "{}"}},
-  {{vsn, "1.0.0"}},
-  {{registered, []}},{}
-  {{applications,
-   [kernel,
-    stdlib,
-    gleam_stdlib
-   ]}},
-  {{env,[]}},
-  {{modules, []}},
-
-  {{licenses, ["Apache 2.0"]}},
-  {{links, []}}
-]}}.
-"#,
-        name, description, module,
-    )
-}
-
 fn src_app(name: &str) -> String {
     format!(
         r#"-module({}_app).
@@ -517,3 +503,59 @@ fn app_rebar_config(name: &str) -> String {
         name
     ))
 }
+
+#[test]
+fn create_test() {
+    let dir = std::env::temp_dir().join("gleam_new_create_test");
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("my_app");
+
+    create(
+        Template::App,
+        "my_app".to_string(),
+        None,
+        Some(path.to_string_lossy().to_string()),
+        "0.0.0",
+    )
+    .unwrap();
+    assert!(path.join("gleam.toml").is_file());
+    assert!(path.join("src/my_app.gleam").is_file());
+    assert!(path.join("src/my_app_app.erl").is_file());
+    assert!(!path.join("src/my_app.app.src").exists());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn app_template_build_test() {
+    use crate::project::{self, ModuleOrigin};
+
+    let dir = std::env::temp_dir().join("gleam_new_app_template_build_test");
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("my_app");
+    create(
+        Template::App,
+        "my_app".to_string(),
+        None,
+        Some(path.to_string_lossy().to_string()),
+        "0.0.0",
+    )
+    .unwrap();
+
+    // The application's start module is compiled and listed in the .app file
+    let (config, _) = crate::read_project_config(&path.to_string_lossy()).unwrap();
+    let mut srcs = vec![];
+    project::collect_source(path.join("src"), ModuleOrigin::Src, &mut srcs, &mut vec![]).unwrap();
+    let analysed = project::analysed(srcs, &[]).unwrap();
+    let erlang_files: Vec<_> = project::erlang_files(&path.join("src")).collect();
+    let mut files = vec![];
+    project::generate_app_file(&config, &analysed, &erlang_files, &path, &mut files);
+
+    assert_eq!(vec![path.join("src/my_app_app.erl")], erlang_files);
+    assert!(files[0].text.contains("{modules, [my_app, my_app_app]}"));
+    assert!(files[0].text.contains("{mod, {my_app_app, []}}"));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,475 @@
+//! The public API of a package, exported as JSON for tooling such as API
+//! browsers. Types are taken from each module's `typ::Module` and printed as
+//! they would be written in Gleam, and documentation from the typed AST.
+//!
+//! Maps are ordered by name so that the output is stable between builds.
+
+pub mod diff;
+
+use crate::{
+    ast::{Statement, TypedStatement},
+    error::{Error, FileIOAction, FileKind, GleamExpect},
+    project::{Analysed, ModuleOrigin, ProjectConfig},
+    typ::{self, pretty::Printer},
+};
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::Arc;
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct PackageInterface {
+    pub name: String,
+    pub version: Option<String>,
+    pub modules: BTreeMap<String, ModuleInterface>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize, Default)]
+pub struct ModuleInterface {
+    pub documentation: Option<String>,
+    pub types: BTreeMap<String, TypeInterface>,
+    pub type_aliases: BTreeMap<String, TypeAliasInterface>,
+    pub functions: BTreeMap<String, FunctionInterface>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct TypeInterface {
+    pub documentation: Option<String>,
+    pub deprecation: Option<String>,
+    pub parameters: Vec<String>,
+    /// The constructors in the order they are defined. External types have
+    /// none.
+    pub constructors: Vec<ConstructorInterface>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct ConstructorInterface {
+    pub name: String,
+    pub documentation: Option<String>,
+    pub parameters: Vec<ParameterInterface>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct TypeAliasInterface {
+    pub documentation: Option<String>,
+    pub deprecation: Option<String>,
+    pub parameters: Vec<String>,
+    #[serde(rename = "type")]
+    pub typ: String,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct FunctionInterface {
+    pub documentation: Option<String>,
+    pub deprecation: Option<String>,
+    pub parameters: Vec<ParameterInterface>,
+    pub return_type: String,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct ParameterInterface {
+    pub label: Option<String>,
+    #[serde(rename = "type")]
+    pub typ: String,
+}
+
+impl PackageInterface {
+    /// The interface of the src modules of the package. Test modules and
+    /// dependencies are not part of its API.
+    ///
+    pub fn new(config: &ProjectConfig, analysed: &[Analysed]) -> Self {
+        let modules = analysed
+            .iter()
+            .filter(|module| module.origin == ModuleOrigin::Src)
+            .map(|module| (module.name.join("/"), ModuleInterface::new(module)))
+            .collect();
+
+        Self {
+            name: config.name.clone(),
+            version: config.version.clone(),
+            modules,
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        let mut json = serde_json::to_string_pretty(self).expect("package interface to_json");
+        json.push('\n');
+        json
+    }
+
+    /// Read an interface written by `gleam export package-interface`.
+    ///
+    pub fn read(path: &Path) -> Result<Self, Error> {
+        let json = std::fs::read_to_string(path).map_err(|e| Error::FileIO {
+            action: FileIOAction::Read,
+            kind: FileKind::File,
+            path: path.to_path_buf(),
+            err: Some(e.to_string()),
+        })?;
+        serde_json::from_str(&json).map_err(|e| Error::InvalidPackageInterface {
+            path: path.to_path_buf(),
+            error: e.to_string(),
+        })
+    }
+}
+
+impl ModuleInterface {
+    fn new(module: &Analysed) -> Self {
+        let documentation = Some(module.ast.documentation.iter().join("\n"))
+            .filter(|documentation| !documentation.is_empty());
+        let mut interface = Self {
+            documentation: documentation_text(&documentation),
+            ..Default::default()
+        };
+        for statement in module.ast.statements.iter() {
+            interface.insert(statement, &module.type_info);
+        }
+        interface
+    }
+
+    fn insert(&mut self, statement: &TypedStatement, info: &typ::Module) {
+        let type_constructor = |name: &str| {
+            info.types
+                .get(name)
+                .gleam_expect("ModuleInterface.insert(): Unable to find type for name")
+        };
+        let value = |name: &str| {
+            info.values
+                .get(name)
+                .gleam_expect("ModuleInterface.insert(): Unable to find value for name")
+        };
+
+        match statement {
+            Statement::Fn {
+                public: true,
+                name,
+                doc,
+                deprecation,
+                ..
+            }
+            | Statement::ExternalFn {
+                public: true,
+                name,
+                doc,
+                deprecation,
+                ..
+            } => {
+                let mut printer = Printer::new();
+                let (parameters, return_type) = signature(value(name), &mut printer);
+                let _ = self.functions.insert(
+                    name.clone(),
+                    FunctionInterface {
+                        documentation: documentation_text(doc),
+                        deprecation: deprecation.clone(),
+                        parameters,
+                        return_type,
+                    },
+                );
+            }
+
+            Statement::CustomType {
+                public: true,
+                name,
+                constructors,
+                doc,
+                deprecation,
+                ..
+            } => {
+                // The type's parameters are printed first so that they are
+                // named in order, with the constructors using the same names
+                let mut printer = Printer::new();
+                let parameters = type_parameters(type_constructor(name), &mut printer);
+                let constructors = constructors
+                    .iter()
+                    .map(|constructor| ConstructorInterface {
+                        name: constructor.name.clone(),
+                        documentation: documentation_text(&constructor.documentation),
+                        parameters: parameters_of(value(&constructor.name), &mut printer).0,
+                    })
+                    .collect();
+                let _ = self.types.insert(
+                    name.clone(),
+                    TypeInterface {
+                        documentation: documentation_text(doc),
+                        deprecation: deprecation.clone(),
+                        parameters,
+                        constructors,
+                    },
+                );
+            }
+
+            Statement::ExternalType {
+                public: true,
+                name,
+                doc,
+                ..
+            } => {
+                let mut printer = Printer::new();
+                let _ = self.types.insert(
+                    name.clone(),
+                    TypeInterface {
+                        documentation: documentation_text(doc),
+                        deprecation: None,
+                        parameters: type_parameters(type_constructor(name), &mut printer),
+                        constructors: vec![],
+                    },
+                );
+            }
+
+            Statement::TypeAlias {
+                public: true,
+                alias,
+                doc,
+                deprecation,
+                ..
+            } => {
+                let mut printer = Printer::new();
+                let constructor = type_constructor(alias);
+                let parameters = type_parameters(constructor, &mut printer);
+                let _ = self.type_aliases.insert(
+                    alias.clone(),
+                    TypeAliasInterface {
+                        documentation: documentation_text(doc),
+                        deprecation: deprecation.clone(),
+                        parameters,
+                        typ: print(&constructor.typ, &mut printer),
+                    },
+                );
+            }
+
+            _ => (),
+        }
+    }
+}
+
+fn type_parameters(constructor: &typ::TypeConstructor, printer: &mut Printer) -> Vec<String> {
+    constructor
+        .parameters
+        .iter()
+        .map(|parameter| print(parameter, printer))
+        .collect()
+}
+
+fn signature(
+    value: &typ::ValueConstructor,
+    printer: &mut Printer,
+) -> (Vec<ParameterInterface>, String) {
+    let (parameters, return_type) = parameters_of(value, printer);
+    (parameters, print(&return_type, printer))
+}
+
+/// The labelled parameters of a function or record constructor, and the type
+/// it returns.
+///
+fn parameters_of(
+    value: &typ::ValueConstructor,
+    printer: &mut Printer,
+) -> (Vec<ParameterInterface>, Arc<typ::Type>) {
+    let (args, retrn) = match value.typ.as_ref() {
+        typ::Type::Fn { args, retrn } => (args.as_slice(), retrn.clone()),
+        _ => (&[][..], value.typ.clone()),
+    };
+    let labels = value
+        .field_map()
+        .map(|field_map| field_map.labels())
+        .unwrap_or_else(|| vec![None; args.len()]);
+
+    let parameters = args
+        .iter()
+        .zip(labels)
+        .map(|(arg, label)| ParameterInterface {
+            label,
+            typ: print(arg, printer),
+        })
+        .collect();
+    (parameters, retrn)
+}
+
+/// Documentation comments with the space following each `///` removed.
+///
+fn documentation_text(doc: &Option<String>) -> Option<String> {
+    doc.as_ref().map(|doc| {
+        doc.split('\n')
+            .map(|line| line.strip_prefix(' ').unwrap_or(line))
+            .join("\n")
+    })
+}
+
+fn print(typ: &typ::Type, printer: &mut Printer) -> String {
+    printer.pretty_print(typ, 0)
+}
+
+#[test]
+fn package_interface_test() {
+    use crate::project::Input;
+    use std::path::PathBuf;
+
+    let src = "//// Things and boxes
+
+/// A box
+pub type Box(a) {
+  /// An empty box
+  Empty
+  Full(contents: a, Int)
+}
+
+pub external type Handle
+
+@deprecated(\"use Box\")
+pub type Crate(b) = Box(b)
+
+/// Open it
+pub fn open(box: Box(x), with handle: Handle) -> Result(x, Nil) {
+  case box {
+    Full(contents: contents, ..) -> Ok(contents)
+    Empty -> Error(Nil)
+  }
+}
+
+pub external fn close(Handle) -> Nil = \"things\" \"close\"
+
+fn private() { 1 }
+";
+    let inputs = vec![
+        Input {
+            origin: ModuleOrigin::Src,
+            path: PathBuf::from("/src/things/box.gleam"),
+            source_base_path: PathBuf::from("/src"),
+            src: src.to_string(),
+        },
+        Input {
+            origin: ModuleOrigin::Test,
+            path: PathBuf::from("/test/box_test.gleam"),
+            source_base_path: PathBuf::from("/test"),
+            src: "pub fn main() { 1 }".to_string(),
+        },
+    ];
+    let analysed = crate::project::analysed(inputs, &[]).expect("should analyse");
+    let config = ProjectConfig {
+        name: "things".to_string(),
+        version: Some("1.0.0".to_string()),
+        ..Default::default()
+    };
+    let interface = PackageInterface::new(&config, &analysed);
+
+    assert_eq!(
+        vec!["things/box"],
+        interface.modules.keys().collect::<Vec<_>>()
+    );
+    let module = &interface.modules["things/box"];
+    assert_eq!(
+        vec!["close", "open"],
+        module.functions.keys().collect::<Vec<_>>()
+    );
+    assert_eq!(
+        FunctionInterface {
+            documentation: Some("Open it".to_string()),
+            deprecation: None,
+            parameters: vec![
+                ParameterInterface {
+                    label: None,
+                    typ: "Box(a)".to_string(),
+                },
+                ParameterInterface {
+                    label: Some("with".to_string()),
+                    typ: "Handle".to_string(),
+                },
+            ],
+            return_type: "Result(a, Nil)".to_string(),
+        },
+        module.functions["open"]
+    );
+
+    assert_eq!(
+        r#"{
+  "name": "things",
+  "version": "1.0.0",
+  "modules": {
+    "things/box": {
+      "documentation": "Things and boxes",
+      "types": {
+        "Box": {
+          "documentation": "A box",
+          "deprecation": null,
+          "parameters": [
+            "a"
+          ],
+          "constructors": [
+            {
+              "name": "Empty",
+              "documentation": "An empty box",
+              "parameters": []
+            },
+            {
+              "name": "Full",
+              "documentation": null,
+              "parameters": [
+                {
+                  "label": "contents",
+                  "type": "a"
+                },
+                {
+                  "label": null,
+                  "type": "Int"
+                }
+              ]
+            }
+          ]
+        },
+        "Handle": {
+          "documentation": null,
+          "deprecation": null,
+          "parameters": [],
+          "constructors": []
+        }
+      },
+      "type_aliases": {
+        "Crate": {
+          "documentation": null,
+          "deprecation": "use Box",
+          "parameters": [
+            "a"
+          ],
+          "type": "Box(a)"
+        }
+      },
+      "functions": {
+        "close": {
+          "documentation": null,
+          "deprecation": null,
+          "parameters": [
+            {
+              "label": null,
+              "type": "Handle"
+            }
+          ],
+          "return_type": "Nil"
+        },
+        "open": {
+          "documentation": "Open it",
+          "deprecation": null,
+          "parameters": [
+            {
+              "label": null,
+              "type": "Box(a)"
+            },
+            {
+              "label": "with",
+              "type": "Handle"
+            }
+          ],
+          "return_type": "Result(a, Nil)"
+        }
+      }
+    }
+  }
+}
+"#,
+        interface.to_json()
+    );
+
+    let read: PackageInterface =
+        serde_json::from_str(&interface.to_json()).expect("should deserialise");
+    assert_eq!(interface, read);
+}
@@ -0,0 +1,698 @@
+//! Comparison of two package interfaces, classifying each change to the API
+//! by the version bump it requires under semantic versioning.
+//!
+//! The comparison is textual: types are compared as they are printed in the
+//! interfaces, with named types identified by their name alone. The one
+//! exception is a function whose new type is a generalisation of its old one,
+//! such as `fn(Int) -> Int` becoming `fn(a) -> Int`, which is minor as every
+//! existing use of it still type checks. Any other change to a type is
+//! reported as major, even where it would not break other packages, so the
+//! bump required can be over-reported.
+
+use super::{
+    ConstructorInterface, FunctionInterface, ModuleInterface, PackageInterface, ParameterInterface,
+    TypeAliasInterface, TypeInterface,
+};
+use itertools::Itertools;
+use std::collections::{BTreeMap, HashMap};
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub enum Severity {
+    /// Nothing that could break or be used by other packages has changed.
+    Patch,
+    /// Something has been added that other packages can use.
+    Minor,
+    /// Something other packages may be using has been removed or changed.
+    Major,
+}
+
+impl Severity {
+    fn text(&self) -> &'static str {
+        match self {
+            Severity::Patch => "patch",
+            Severity::Minor => "minor",
+            Severity::Major => "major",
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Change {
+    pub severity: Severity,
+    pub module: String,
+    pub description: String,
+}
+
+/// The changes to the API from the old interface to the new one, the most
+/// severe first.
+///
+pub fn diff(old: &PackageInterface, new: &PackageInterface) -> Vec<Change> {
+    let mut changes = Changes::default();
+    each_pair(&old.modules, &new.modules, |name, old, new| {
+        match (old, new) {
+            (Some(_), None) => changes.push(Severity::Major, name, "module removed".to_string()),
+            (None, Some(_)) => changes.push(Severity::Minor, name, "module added".to_string()),
+            (Some(old), Some(new)) => changes.module(name, old, new),
+            (None, None) => (),
+        }
+    });
+
+    // The sort is stable so changes of the same severity stay in the order
+    // of the modules and their definitions
+    let mut changes = changes.0;
+    changes.sort_by_key(|change| std::cmp::Reverse(change.severity));
+    changes
+}
+
+/// The bump the changes require, or `None` if nothing has changed.
+///
+pub fn severity(changes: &[Change]) -> Option<Severity> {
+    changes.iter().map(|change| change.severity).max()
+}
+
+/// A human readable report of the changes.
+///
+pub fn report(changes: &[Change]) -> String {
+    let severity = match severity(changes) {
+        None => return "There are no changes to the API.\n".to_string(),
+        Some(severity) => severity,
+    };
+
+    let mut report = String::new();
+    for change in changes {
+        report.push_str(&format!(
+            "{}  {}: {}\n",
+            change.severity.text(),
+            change.module,
+            change.description
+        ));
+    }
+    report.push_str(&format!(
+        "\nThis is a {} change to the API.\n",
+        severity.text()
+    ));
+    report
+}
+
+#[derive(Debug, Default)]
+struct Changes(Vec<Change>);
+
+impl Changes {
+    fn push(&mut self, severity: Severity, module: &str, description: String) {
+        self.0.push(Change {
+            severity,
+            module: module.to_string(),
+            description,
+        })
+    }
+
+    fn module(&mut self, module: &str, old: &ModuleInterface, new: &ModuleInterface) {
+        if old.documentation != new.documentation {
+            self.push(Severity::Patch, module, "documentation changed".to_string());
+        }
+
+        each_pair(&old.types, &new.types, |name, old, new| match (old, new) {
+            (Some(_), None) => {
+                self.push(Severity::Major, module, format!("type `{}` removed", name))
+            }
+            (None, Some(_)) => self.push(Severity::Minor, module, format!("type `{}` added", name)),
+            (Some(old), Some(new)) => self.type_(module, name, old, new),
+            (None, None) => (),
+        });
+
+        each_pair(
+            &old.type_aliases,
+            &new.type_aliases,
+            |name, old, new| match (old, new) {
+                (Some(_), None) => self.push(
+                    Severity::Major,
+                    module,
+                    format!("type alias `{}` removed", name),
+                ),
+                (None, Some(_)) => self.push(
+                    Severity::Minor,
+                    module,
+                    format!("type alias `{}` added", name),
+                ),
+                (Some(old), Some(new)) => self.type_alias(module, name, old, new),
+                (None, None) => (),
+            },
+        );
+
+        each_pair(&old.functions, &new.functions, |name, old, new| {
+            match (old, new) {
+                (Some(_), None) => self.push(
+                    Severity::Major,
+                    module,
+                    format!("function `{}` removed", name),
+                ),
+                (None, Some(_)) => self.push(
+                    Severity::Minor,
+                    module,
+                    format!("function `{}` added", name),
+                ),
+                (Some(old), Some(new)) => self.function(module, name, old, new),
+                (None, None) => (),
+            }
+        });
+    }
+
+    fn type_(&mut self, module: &str, name: &str, old: &TypeInterface, new: &TypeInterface) {
+        let what = format!("type `{}`", name);
+        self.documentation(module, &what, &old.documentation, &new.documentation);
+        self.deprecation(module, &what, &old.deprecation, &new.deprecation);
+        self.type_parameters(module, &what, &old.parameters, &new.parameters);
+
+        // Adding a constructor breaks any case expression that matches on
+        // all of the existing ones, but a type with no constructors, such as
+        // an external type, cannot have been matched on
+        let constructor_added = if old.constructors.is_empty() {
+            Severity::Minor
+        } else {
+            Severity::Major
+        };
+        let old_constructors: BTreeMap<_, _> = old.constructors.iter().map(by_name).collect();
+        let new_constructors: BTreeMap<_, _> = new.constructors.iter().map(by_name).collect();
+        each_pair(
+            &old_constructors,
+            &new_constructors,
+            |constructor, old, new| {
+                let what = format!("constructor `{}` of type `{}`", constructor, name);
+                match (old, new) {
+                    (Some(_), None) => {
+                        self.push(Severity::Major, module, format!("{} removed", what))
+                    }
+                    (None, Some(_)) => {
+                        self.push(constructor_added, module, format!("{} added", what))
+                    }
+                    (Some(old), Some(new)) => {
+                        self.documentation(module, &what, &old.documentation, &new.documentation);
+                        self.parameters(module, &what, &old.parameters, &new.parameters);
+                    }
+                    (None, None) => (),
+                }
+            },
+        );
+    }
+
+    fn type_alias(
+        &mut self,
+        module: &str,
+        name: &str,
+        old: &TypeAliasInterface,
+        new: &TypeAliasInterface,
+    ) {
+        let what = format!("type alias `{}`", name);
+        self.documentation(module, &what, &old.documentation, &new.documentation);
+        self.deprecation(module, &what, &old.deprecation, &new.deprecation);
+        self.type_parameters(module, &what, &old.parameters, &new.parameters);
+        if old.typ != new.typ {
+            self.push(
+                Severity::Major,
+                module,
+                format!("{} changed from `{}` to `{}`", what, old.typ, new.typ),
+            );
+        }
+    }
+
+    fn function(
+        &mut self,
+        module: &str,
+        name: &str,
+        old: &FunctionInterface,
+        new: &FunctionInterface,
+    ) {
+        let what = format!("function `{}`", name);
+        self.documentation(module, &what, &old.documentation, &new.documentation);
+        self.deprecation(module, &what, &old.deprecation, &new.deprecation);
+
+        if let (Some(old_type), Some(new_type)) = (function_shape(old), function_shape(new)) {
+            if old_type != new_type && new_type.generalises(&old_type, &mut HashMap::new()) {
+                self.push(
+                    Severity::Minor,
+                    module,
+                    format!(
+                        "type of {} generalised from `{}` to `{}`",
+                        what,
+                        function_type(old),
+                        function_type(new)
+                    ),
+                );
+                for (index, (old, new)) in old.parameters.iter().zip(&new.parameters).enumerate() {
+                    self.label(module, &what, index + 1, &old.label, &new.label);
+                }
+                return;
+            }
+        }
+
+        self.parameters(module, &what, &old.parameters, &new.parameters);
+        if old.return_type != new.return_type {
+            self.push(
+                Severity::Major,
+                module,
+                format!(
+                    "return type of {} changed from `{}` to `{}`",
+                    what, old.return_type, new.return_type
+                ),
+            );
+        }
+    }
+
+    fn parameters(
+        &mut self,
+        module: &str,
+        what: &str,
+        old: &[ParameterInterface],
+        new: &[ParameterInterface],
+    ) {
+        if old.len() != new.len() {
+            self.push(
+                Severity::Major,
+                module,
+                format!(
+                    "arity of {} changed from {} to {}",
+                    what,
+                    old.len(),
+                    new.len()
+                ),
+            );
+            return;
+        }
+
+        for (index, (old, new)) in old.iter().zip(new).enumerate() {
+            let position = index + 1;
+            if old.typ != new.typ {
+                self.push(
+                    Severity::Major,
+                    module,
+                    format!(
+                        "type of parameter {} of {} changed from `{}` to `{}`",
+                        position, what, old.typ, new.typ
+                    ),
+                );
+            }
+            self.label(module, what, position, &old.label, &new.label);
+        }
+    }
+
+    fn label(
+        &mut self,
+        module: &str,
+        what: &str,
+        position: usize,
+        old: &Option<String>,
+        new: &Option<String>,
+    ) {
+        match (old, new) {
+            (Some(old), None) => self.push(
+                Severity::Major,
+                module,
+                format!(
+                    "label `{}` of parameter {} of {} removed",
+                    old, position, what
+                ),
+            ),
+            // Existing calls without the label still work
+            (None, Some(new)) => self.push(
+                Severity::Minor,
+                module,
+                format!(
+                    "label `{}` added to parameter {} of {}",
+                    new, position, what
+                ),
+            ),
+            (Some(old), Some(new)) if old != new => self.push(
+                Severity::Major,
+                module,
+                format!(
+                    "label of parameter {} of {} changed from `{}` to `{}`",
+                    position, what, old, new
+                ),
+            ),
+            _ => (),
+        }
+    }
+
+    fn type_parameters(&mut self, module: &str, what: &str, old: &[String], new: &[String]) {
+        if old.len() != new.len() {
+            self.push(
+                Severity::Major,
+                module,
+                format!(
+                    "number of type parameters of {} changed from {} to {}",
+                    what,
+                    old.len(),
+                    new.len()
+                ),
+            );
+        }
+    }
+
+    fn documentation(
+        &mut self,
+        module: &str,
+        what: &str,
+        old: &Option<String>,
+        new: &Option<String>,
+    ) {
+        if old != new {
+            self.push(
+                Severity::Patch,
+                module,
+                format!("documentation of {} changed", what),
+            );
+        }
+    }
+
+    fn deprecation(
+        &mut self,
+        module: &str,
+        what: &str,
+        old: &Option<String>,
+        new: &Option<String>,
+    ) {
+        match (old, new) {
+            (None, Some(_)) => self.push(Severity::Minor, module, format!("{} deprecated", what)),
+            // Nothing is added to or removed from the API
+            (Some(_), None) => self.push(
+                Severity::Patch,
+                module,
+                format!("{} no longer deprecated", what),
+            ),
+            _ => (),
+        }
+    }
+}
+
+fn by_name(constructor: &ConstructorInterface) -> (&String, &ConstructorInterface) {
+    (&constructor.name, constructor)
+}
+
+fn function_type(function: &FunctionInterface) -> String {
+    format!(
+        "fn({}) -> {}",
+        function
+            .parameters
+            .iter()
+            .map(|p| p.typ.as_str())
+            .join(", "),
+        function.return_type
+    )
+}
+
+fn function_shape(function: &FunctionInterface) -> Option<Shape> {
+    let mut args = function
+        .parameters
+        .iter()
+        .map(|parameter| Shape::parse(&parameter.typ))
+        .collect::<Option<Vec<_>>>()?;
+    args.push(Shape::parse(&function.return_type)?);
+    Some(Shape::App("fn".to_string(), args))
+}
+
+/// A type as printed in an interface, parsed only so far as to tell type
+/// variables apart from the types they may stand for. Function and tuple
+/// types are applications of `fn` and `tuple`, with the return type of a
+/// function as its last argument.
+///
+#[derive(Debug, PartialEq)]
+enum Shape {
+    Var(String),
+    App(String, Vec<Shape>),
+}
+
+impl Shape {
+    fn parse(text: &str) -> Option<Self> {
+        let mut tokens = shape_tokens(text).into_iter().peekable();
+        let shape = Self::parse_tokens(&mut tokens)?;
+        match tokens.next() {
+            None => Some(shape),
+            Some(_) => None,
+        }
+    }
+
+    fn parse_tokens(
+        tokens: &mut std::iter::Peekable<impl Iterator<Item = String>>,
+    ) -> Option<Self> {
+        let name = tokens.next()?;
+        if tokens.peek().map(String::as_str) != Some("(") {
+            return match name.chars().next() {
+                Some(c) if c.is_lowercase() => Some(Shape::Var(name)),
+                Some(c) if c.is_uppercase() => Some(Shape::App(name, vec![])),
+                _ => None,
+            };
+        }
+
+        let _ = tokens.next();
+        let mut args = vec![];
+        loop {
+            if tokens.peek().map(String::as_str) == Some(")") {
+                let _ = tokens.next();
+                break;
+            }
+            args.push(Self::parse_tokens(tokens)?);
+            match tokens.next()?.as_str() {
+                "," => (),
+                ")" => break,
+                _ => return None,
+            }
+        }
+
+        if name == "fn" {
+            if tokens.next()? != "->" {
+                return None;
+            }
+            args.push(Self::parse_tokens(tokens)?);
+        }
+        Some(Shape::App(name, args))
+    }
+
+    /// Whether replacing the type variables of this type, each with the same
+    /// type wherever it appears, can give the `specific` type.
+    ///
+    fn generalises<'a>(
+        &'a self,
+        specific: &'a Self,
+        vars: &mut HashMap<&'a str, &'a Self>,
+    ) -> bool {
+        match self {
+            Shape::Var(name) => match vars.get(name.as_str()) {
+                Some(typ) => *typ == specific,
+                None => {
+                    let _ = vars.insert(name, specific);
+                    true
+                }
+            },
+            Shape::App(name, args) => match specific {
+                Shape::App(specific_name, specific_args) => {
+                    name == specific_name
+                        && args.len() == specific_args.len()
+                        && args
+                            .iter()
+                            .zip(specific_args)
+                            .all(|(arg, specific_arg)| arg.generalises(specific_arg, vars))
+                }
+                Shape::Var(_) => false,
+            },
+        }
+    }
+}
+
+fn shape_tokens(text: &str) -> Vec<String> {
+    let mut tokens = vec![];
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '(' | ')' | ',' => tokens.push(c.to_string()),
+            '-' if chars.peek() == Some(&'>') => {
+                let _ = chars.next();
+                tokens.push("->".to_string());
+            }
+            c if c.is_whitespace() => (),
+            c => {
+                let mut token = c.to_string();
+                while let Some(&c) = chars.peek() {
+                    if c.is_alphanumeric() || c == '_' {
+                        token.push(c);
+                        let _ = chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                tokens.push(token);
+            }
+        }
+    }
+    tokens
+}
+
+/// Call `f` with each key found in either map along with its value in each.
+///
+fn each_pair<'a, K: Ord, V>(
+    old: &'a BTreeMap<K, V>,
+    new: &'a BTreeMap<K, V>,
+    mut f: impl FnMut(&'a K, Option<&'a V>, Option<&'a V>),
+) {
+    for key in old.keys().merge(new.keys()).dedup() {
+        f(key, old.get(key), new.get(key))
+    }
+}
+
+#[test]
+fn diff_test() {
+    use crate::project::{Input, ModuleOrigin, ProjectConfig};
+    use std::path::PathBuf;
+
+    let interface = |src: &str| {
+        let inputs = vec![Input {
+            origin: ModuleOrigin::Src,
+            path: PathBuf::from("/src/box.gleam"),
+            source_base_path: PathBuf::from("/src"),
+            src: src.to_string(),
+        }];
+        let analysed = crate::project::analysed(inputs, &[]).expect("should analyse");
+        PackageInterface::new(&ProjectConfig::default(), &analysed)
+    };
+    let changes = |old: &str, new: &str| {
+        diff(&interface(old), &interface(new))
+            .into_iter()
+            .map(|change| format!("{} {}", change.severity.text(), change.description))
+            .collect::<Vec<_>>()
+    };
+
+    let src = "pub type Box(a) { Box(a) }
+pub fn open(box: Box(a), with key: Int) -> a { let Box(x) = box x }";
+    assert_eq!(Vec::<String>::new(), changes(src, src));
+    assert_eq!(None, severity(&diff(&interface(src), &interface(src))));
+
+    // Removals and changes to what exists are major
+    assert_eq!(
+        vec![
+            "major function `open` removed",
+            "minor function `close` added",
+        ],
+        changes(
+            src,
+            "pub type Box(a) { Box(a) }
+pub fn close(box: Box(a)) -> Box(a) { box }"
+        )
+    );
+    assert_eq!(
+        vec![
+            "major type of parameter 2 of function `open` changed from `Int` to `String`",
+            "major label of parameter 2 of function `open` changed from `with` to `using`",
+            "major return type of function `open` changed from `a` to `Box(a)`",
+        ],
+        changes(
+            src,
+            "pub type Box(a) { Box(a) }
+pub fn open(box: Box(a), using key: String) -> Box(a) { box }"
+        )
+    );
+    assert_eq!(
+        vec!["major arity of function `open` changed from 2 to 1"],
+        changes(
+            src,
+            "pub type Box(a) { Box(a) }
+pub fn open(box: Box(a)) -> a { let Box(x) = box x }"
+        )
+    );
+
+    // Adding a constructor breaks exhaustive case expressions
+    assert_eq!(
+        vec![
+            "major number of type parameters of type `Box` changed from 1 to 2",
+            "major arity of constructor `Box` of type `Box` changed from 1 to 2",
+            "major constructor `Empty` of type `Box` added",
+            "major type of parameter 1 of function `open` changed from `Box(a)` to `Box(a, b)`",
+            "major return type of function `open` changed from `a` to `b`",
+        ],
+        changes(
+            src,
+            "pub type Box(a, b) { Box(a, b) Empty }
+pub fn open(box: Box(a, b), with key: Int) -> b { let Box(_, x) = box x }"
+        )
+    );
+
+    // Constructors given to a type that had none cannot break existing case
+    // expressions
+    assert_eq!(
+        vec![
+            "minor constructor `One` of type `Thing` added",
+            "minor constructor `Two` of type `Thing` added",
+        ],
+        changes("pub external type Thing", "pub type Thing { One Two }")
+    );
+
+    // Labels may be added to existing parameters, and documentation changes
+    // do not affect users
+    assert_eq!(
+        vec![
+            "minor function `open` deprecated",
+            "minor label `box` added to parameter 1 of function `open`",
+            "patch documentation of type `Box` changed",
+        ],
+        changes(
+            src,
+            "/// A box
+pub type Box(a) { Box(a) }
+@deprecated(\"use close\")
+pub fn open(box box: Box(a), with key: Int) -> a { let Box(x) = box x }"
+        )
+    );
+    assert_eq!(
+        vec!["patch function `open` no longer deprecated"],
+        changes(
+            "pub type Box(a) { Box(a) }
+@deprecated(\"use close\")
+pub fn open(box: Box(a), with key: Int) -> a { let Box(x) = box x }",
+            src
+        )
+    );
+
+    // A function may be made more general as existing uses still type check,
+    // but only if each of its new type variables stands for a single type
+    assert_eq!(
+        vec!["minor type of function `f` generalised from `fn(Int) -> Int` to `fn(a) -> Int`"],
+        changes(
+            "pub fn f(x: Int) -> Int { 1 }",
+            "pub fn f(x: a) -> Int { 1 }"
+        )
+    );
+    assert_eq!(
+        vec![
+            "minor type of function `f` generalised from `fn(List(Int), Int) -> Int` to `fn(List(a), a) -> a`"
+        ],
+        changes(
+            "pub fn f(x: List(Int), y: Int) -> Int { y }",
+            "pub fn f(x: List(a), y: a) -> a { y }"
+        )
+    );
+    assert_eq!(
+        vec!["major type of parameter 2 of function `f` changed from `b` to `a`"],
+        changes(
+            "pub fn f(x: a, y: b) -> a { x }",
+            "pub fn f(x: a, y: a) -> a { x }"
+        )
+    );
+    assert_eq!(
+        vec!["major type of parameter 1 of function `f` changed from `a` to `Int`"],
+        changes(
+            "pub fn f(x: a) -> Int { 1 }",
+            "pub fn f(x: Int) -> Int { 1 }"
+        )
+    );
+
+    let changes = diff(&interface(src), &interface("pub fn new() { 1 }"));
+    assert_eq!(Some(Severity::Major), severity(&changes));
+    assert_eq!(
+        "major  box: type `Box` removed
+major  box: function `open` removed
+minor  box: function `new` added
+
+This is a major change to the API.
+",
+        report(&changes)
+    );
+    assert_eq!("There are no changes to the API.\n", report(&[]));
+}
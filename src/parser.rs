@@ -6,6 +6,10 @@ pub enum Error {
         location: crate::ast::SrcSpan,
         count: usize,
     },
+
+    InvalidClauseGuardModuleSelect {
+        location: crate::ast::SrcSpan,
+    },
 }
 
 pub type LalrpopError = lalrpop_util::ParseError<usize, (usize, String), Error>;
@@ -151,7 +155,8 @@ pub fn strip_extra(src: &str) -> (String, ModuleComments<'_>) {
 
                     chomp_newlines(&mut buffer, start, &mut comments, &mut chars)
                 }
-                _ => buffer.push(' '),
+                // Keep byte offsets the same as in the original source
+                _ => buffer.push_str(&" ".repeat(c.len())),
             },
         }
     }
@@ -217,6 +222,7 @@ fn strip_extra_test() {
     assert_stripped!(r#""\"//" hi"#, r#""\"//" hi"#);
     assert_stripped!("/// Something\n", "             \n");
     assert_stripped!(" /// Something\n", "              \n");
+    assert_stripped!("// é\nx\n", "     \nx\n");
     assert_stripped!(
         "/// Something
 /// Something else
@@ -243,7 +249,7 @@ pub external fn a() -> Nil =
     // Testing comment collection
     assert_stripped!(
         "// 👨‍👩‍👧‍👧 unicode\n",
-        "            \n",
+        "                                    \n",
         ModuleComments {
             module_comments: vec![],
             doc_comments: vec![],
@@ -426,6 +432,32 @@ pub fn seq(mut exprs: Vec<crate::ast::UntypedExpr>) -> crate::ast::UntypedExpr {
         })
 }
 
+/// Parse the value of a positive integer literal, which may contain underscores
+/// and be written in hexadecimal, octal, or binary with a `0x`, `0o`, or `0b`
+/// prefix.
+///
+pub fn parse_int(literal: &str) -> u64 {
+    let digits = literal.replace("_", "");
+    let (radix, digits) = match digits.get(0..2) {
+        Some("0x") => (16, &digits[2..]),
+        Some("0o") => (8, &digits[2..]),
+        Some("0b") => (2, &digits[2..]),
+        _ => (10, digits.as_str()),
+    };
+    u64::from_str_radix(digits, radix).unwrap()
+}
+
 pub fn location(start: usize, end: usize) -> crate::ast::SrcSpan {
     crate::ast::SrcSpan { start, end }
 }
+
+#[test]
+fn parse_int_test() {
+    assert_eq!(parse_int("0"), 0);
+    assert_eq!(parse_int("1_000_000"), 1_000_000);
+    assert_eq!(parse_int("0xFF"), 255);
+    assert_eq!(parse_int("0xff"), 255);
+    assert_eq!(parse_int("0o17"), 15);
+    assert_eq!(parse_int("0b1010"), 10);
+    assert_eq!(parse_int("0b1111_0000"), 240);
+}
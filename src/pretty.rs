@@ -79,6 +79,14 @@ pub enum Document {
     /// A mandatory linebreak
     Line(usize),
 
+    /// A mandatory linebreak, followed by as many blank lines as are needed
+    /// for the next line to have the given line number. If the output is
+    /// already past that line this is a single linebreak.
+    LineTo(usize),
+
+    /// Sets the line number of the current line, as used by `LineTo`
+    LineNumber(usize),
+
     /// Forces contained groups to break
     ForceBreak,
 
@@ -119,9 +127,9 @@ fn fits(mut limit: isize, mut docs: Vector<(isize, Mode, Document)>) -> bool {
         };
 
         match document {
-            Document::Nil => (),
+            Document::Nil | Document::LineNumber(_) => (),
 
-            Document::Line(_) => return true,
+            Document::Line(_) | Document::LineTo(_) => return true,
 
             Document::ForceBreak => return false,
 
@@ -158,16 +166,31 @@ pub fn format(limit: isize, doc: Document) -> String {
 }
 
 fn fmt(b: &mut String, limit: isize, mut width: isize, mut docs: Vector<(isize, Mode, Document)>) {
+    let mut line = 1;
     while let Some((indent, mode, document)) = docs.pop_front() {
         match document {
             Document::Nil | Document::ForceBreak => (),
 
+            Document::LineNumber(i) => line = i,
+
             Document::Line(i) => {
                 for _ in 0..i {
                     b.push_str("\n");
                 }
                 b.push_str(" ".repeat(indent as usize).as_str());
                 width = indent;
+                line += i;
+            }
+
+            Document::LineTo(i) => {
+                b.push('\n');
+                line += 1;
+                while line < i {
+                    b.push('\n');
+                    line += 1;
+                }
+                b.push_str(" ".repeat(indent as usize).as_str());
+                width = indent;
             }
 
             Document::Break { broken, unbroken } => {
@@ -180,6 +203,7 @@ fn fmt(b: &mut String, limit: isize, mut width: isize, mut docs: Vector<(isize,
                         b.push_str(broken.as_str());
                         b.push_str("\n");
                         b.push_str(" ".repeat(indent as usize).as_str());
+                        line += 1;
                         indent as isize
                     }
                 };
@@ -187,6 +211,7 @@ fn fmt(b: &mut String, limit: isize, mut width: isize, mut docs: Vector<(isize,
 
             Document::Text(s) => {
                 width += s.len() as isize;
+                line += s.matches('\n').count();
                 b.push_str(s.as_str());
             }
 
@@ -412,6 +437,25 @@ fn format_test() {
         }),
     );
     assert_eq!("broken\n".to_string(), format(100, doc));
+
+    // LineTo pads with blank lines up to the given line
+    let doc = "1".to_doc().append(line_to(3)).append("3");
+    assert_eq!("1\n\n3".to_string(), format(100, doc));
+
+    // LineTo counts from the line set by LineNumber
+    let doc = line_number(10)
+        .append("10")
+        .append(lines(2))
+        .append("12")
+        .append(line_to(14))
+        .append("14");
+    assert_eq!("10\n\n12\n\n14".to_string(), format(100, doc));
+
+    // LineTo breaks once when already past the given line
+    let doc = line_number(5)
+        .append("5")
+        .append(line_to(2).append("6").nest(2));
+    assert_eq!("5\n  6".to_string(), format(100, doc));
 }
 
 pub fn nil() -> Document {
@@ -426,6 +470,14 @@ pub fn lines(i: usize) -> Document {
     Document::Line(i)
 }
 
+pub fn line_to(i: usize) -> Document {
+    Document::LineTo(i)
+}
+
+pub fn line_number(i: usize) -> Document {
+    Document::LineNumber(i)
+}
+
 pub fn force_break() -> Document {
     Document::ForceBreak
 }
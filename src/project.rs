@@ -1,3 +1,4 @@
+pub mod import_graph;
 mod source_tree;
 #[cfg(test)]
 mod tests;
@@ -5,17 +6,433 @@ mod tests;
 use crate::{
     ast::TypedModule,
     error::{Error, FileIOAction, FileKind, GleamExpect},
+    line_numbers::LineNumbers,
     typ,
     warning::Warning,
 };
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use source_tree::SourceTree;
 use std::collections::HashMap;
 use std::path::PathBuf;
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Serialize, Debug, PartialEq)]
 pub struct ProjectConfig {
     pub name: String,
+    #[serde(default)]
+    pub version: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub licences: Vec<String>,
+    #[serde(default = "default_targets")]
+    pub targets: Vec<Target>,
+    #[serde(default)]
+    pub repository: Repository,
+    #[serde(default)]
+    pub links: Vec<Link>,
+    #[serde(default)]
+    pub dependencies: HashMap<String, Dependency>,
+    #[serde(default, rename = "dev-dependencies")]
+    pub dev_dependencies: HashMap<String, Dependency>,
+    #[serde(default)]
+    pub erlang: ErlangConfig,
+    #[serde(default)]
+    pub docs: DocsConfig,
+    #[serde(default)]
+    pub format: FormatConfig,
+    #[serde(default)]
+    pub forbidden_imports: Vec<ImportRule>,
+    #[serde(default)]
+    pub profiles: HashMap<String, ProfileConfig>,
+}
+
+impl Default for ProjectConfig {
+    fn default() -> Self {
+        Self {
+            name: Default::default(),
+            version: Default::default(),
+            description: Default::default(),
+            licences: Default::default(),
+            targets: default_targets(),
+            repository: Default::default(),
+            links: Default::default(),
+            dependencies: Default::default(),
+            dev_dependencies: Default::default(),
+            erlang: Default::default(),
+            docs: Default::default(),
+            format: Default::default(),
+            forbidden_imports: Default::default(),
+            profiles: Default::default(),
+        }
+    }
+}
+
+impl ProjectConfig {
+    /// The build profile with the given name. The `dev` and `prod` profiles
+    /// always exist and may have their settings overridden in gleam.toml.
+    ///
+    pub fn profile(&self, name: &str) -> Result<BuildProfile, Error> {
+        let base = match name {
+            "dev" => BuildProfile {
+                name: name.to_string(),
+                include_tests: true,
+                warnings_as_errors: false,
+                debug_info: true,
+                gen_dir: PathBuf::from("gen"),
+                docs_dir: PathBuf::from("docs"),
+                build_dir: PathBuf::from("_build").join(name),
+            },
+            _ => BuildProfile {
+                name: name.to_string(),
+                include_tests: false,
+                warnings_as_errors: false,
+                debug_info: false,
+                gen_dir: PathBuf::from("gen"),
+                docs_dir: PathBuf::from("docs"),
+                build_dir: PathBuf::from("_build").join(name),
+            },
+        };
+
+        let config = match self.profiles.get(name) {
+            Some(config) => config,
+            None if name == "dev" || name == "prod" => return Ok(base),
+            None => {
+                let mut profiles: Vec<_> = self
+                    .profiles
+                    .keys()
+                    .cloned()
+                    .chain(vec!["dev".to_string(), "prod".to_string()])
+                    .collect();
+                profiles.sort();
+                profiles.dedup();
+                return Err(Error::UnknownProfile {
+                    name: name.to_string(),
+                    profiles,
+                });
+            }
+        };
+
+        Ok(BuildProfile {
+            name: base.name,
+            include_tests: config.include_tests.unwrap_or(base.include_tests),
+            warnings_as_errors: config.warnings_as_errors.unwrap_or(base.warnings_as_errors),
+            debug_info: config.debug_info.unwrap_or(base.debug_info),
+            gen_dir: config.gen_dir.clone().unwrap_or(base.gen_dir),
+            docs_dir: config.docs_dir.clone().unwrap_or(base.docs_dir),
+            build_dir: config.build_dir.clone().unwrap_or(base.build_dir),
+        })
+    }
+}
+
+fn default_targets() -> Vec<Target> {
+    vec![Target::Erlang]
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum Target {
+    Erlang,
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq, Clone)]
+#[serde(untagged)]
+pub enum Dependency {
+    /// A package from Hex, given as a version requirement such as `"~> 0.8"`
+    Hex(String),
+    /// A package stored locally, such as `{ path = "../my_lib" }`
+    Path { path: PathBuf },
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq, Default)]
+pub struct ErlangConfig {
+    /// The module with a `start/2` function that starts the OTP application,
+    /// if the project is an application rather than a library.
+    #[serde(default)]
+    pub application_start_module: Option<String>,
+    /// OTP applications to be started before this one, in addition to the
+    /// project's dependencies.
+    #[serde(default)]
+    pub extra_applications: Vec<String>,
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq, Default)]
+pub struct DocsConfig {
+    /// Include the documentation for the dependencies checked out locally in
+    /// `_checkouts` in the generated site.
+    #[serde(default)]
+    pub include_path_dependencies: bool,
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq, Clone)]
+pub struct FormatConfig {
+    /// The width the formatter tries to keep lines within.
+    #[serde(default = "default_line_width")]
+    pub line_width: usize,
+    /// Sort imports by module path, along with their unqualified imports.
+    /// Imports separated by an empty line are sorted as separate groups.
+    #[serde(default)]
+    pub sort_imports: bool,
+}
+
+impl Default for FormatConfig {
+    fn default() -> Self {
+        Self {
+            line_width: default_line_width(),
+            sort_imports: false,
+        }
+    }
+}
+
+fn default_line_width() -> usize {
+    80
+}
+
+/// A layering rule forbidding the modules under one module path from
+/// importing the modules under another, such as `app/domain` modules
+/// importing `app/web` modules.
+///
+#[derive(Deserialize, Serialize, Debug, PartialEq, Clone)]
+pub struct ImportRule {
+    pub from: String,
+    pub to: String,
+    /// Why the import is forbidden, shown in the error.
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+impl ImportRule {
+    pub fn forbids(&self, module: &str, import: &str) -> bool {
+        is_under(module, &self.from) && is_under(import, &self.to)
+    }
+}
+
+fn is_under(module: &str, path: &str) -> bool {
+    module == path || (module.starts_with(path) && module[path.len()..].starts_with('/'))
+}
+
+/// The settings for a build profile given in gleam.toml. Any that are not
+/// given take the default for the profile.
+///
+#[derive(Deserialize, Serialize, Debug, PartialEq, Default)]
+pub struct ProfileConfig {
+    #[serde(default)]
+    pub include_tests: Option<bool>,
+    #[serde(default)]
+    pub warnings_as_errors: Option<bool>,
+    #[serde(default)]
+    pub debug_info: Option<bool>,
+    #[serde(default)]
+    pub gen_dir: Option<PathBuf>,
+    #[serde(default)]
+    pub docs_dir: Option<PathBuf>,
+    #[serde(default)]
+    pub build_dir: Option<PathBuf>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct BuildProfile {
+    pub name: String,
+    /// Compile the test modules along with the src modules
+    pub include_tests: bool,
+    /// Fail the build if there are any warnings
+    pub warnings_as_errors: bool,
+    /// Include debug info in the compiled BEAM files
+    pub debug_info: bool,
+    /// Where the generated Erlang is written, relative to each package
+    pub gen_dir: PathBuf,
+    /// Where the HTML documentation is written, relative to the project
+    pub docs_dir: PathBuf,
+    /// Where dependencies and compiled BEAM files are found, relative to the
+    /// project
+    pub build_dir: PathBuf,
+}
+
+/// Parse the contents of a gleam.toml file, returning the config and warnings
+/// for any keys that are not part of the config.
+///
+pub fn parse_config(path: PathBuf, src: String) -> Result<(ProjectConfig, Vec<Warning>), Error> {
+    let config: ProjectConfig = match toml::from_str(&src) {
+        Ok(config) => config,
+        Err(e) => {
+            // The line and column are shown by the diagnostic so are removed
+            // from the message
+            let error = e.to_string();
+            let error = error.split(" at line ").next().unwrap_or("").to_string();
+
+            // The position toml gives for an invalid value is the start of the
+            // table containing it, so where possible we find the key instead
+            let location = error
+                .rsplit(" for key `")
+                .next()
+                .filter(|_| error.contains(" for key `"))
+                .and_then(|key| config_key_location(&src, key.trim_end_matches('`')))
+                .or_else(|| {
+                    e.line_col().map(|(line, column)| {
+                        let start = src
+                            .split('\n')
+                            .take(line)
+                            .map(|line| line.len() + 1)
+                            .sum::<usize>()
+                            + column;
+                        let start = start.min(src.len());
+                        crate::ast::SrcSpan {
+                            start,
+                            end: (start + 1).min(src.len()),
+                        }
+                    })
+                });
+
+            return Err(Error::InvalidProjectConfig {
+                path,
+                src,
+                location,
+                error,
+            });
+        }
+    };
+
+    // Any key that does not survive a round trip through the config struct is
+    // not one we know about
+    let mut unknown = vec![];
+    if let (Ok(given), Ok(known)) = (
+        toml::from_str::<toml::Value>(&src),
+        toml::Value::try_from(&config),
+    ) {
+        unknown_config_keys(&mut vec![], &given, &known, &mut unknown);
+    }
+    let warnings = unknown
+        .into_iter()
+        .map(|key| Warning::UnknownConfigKey {
+            path: path.clone(),
+            key,
+        })
+        .collect();
+
+    Ok((config, warnings))
+}
+
+/// Find where a dotted key such as `docs.include_path_dependencies` is
+/// assigned in a gleam.toml file.
+///
+fn config_key_location(src: &str, key: &str) -> Option<crate::ast::SrcSpan> {
+    lazy_static! {
+        static ref TABLE: regex::Regex =
+            regex::Regex::new(r"^\s*\[\[?\s*([^\]]+?)\s*\]\]?").gleam_expect("config table regex");
+        static ref ASSIGNMENT: regex::Regex =
+            regex::Regex::new(r"^\s*([^=\s]+)\s*=").gleam_expect("config assignment regex");
+    }
+
+    let mut table = String::new();
+    let mut offset = 0;
+    for line in src.split('\n') {
+        if let Some(captures) = TABLE.captures(line) {
+            table = format!("{}.", &captures[1]);
+        } else if let Some(captures) = ASSIGNMENT.captures(line) {
+            let name = captures.get(1).gleam_expect("config assignment capture");
+            if format!("{}{}", table, name.as_str()) == key {
+                return Some(crate::ast::SrcSpan {
+                    start: offset + name.start(),
+                    end: offset + name.end(),
+                });
+            }
+        }
+        offset += line.len() + 1;
+    }
+    None
+}
+
+fn unknown_config_keys(
+    prefix: &mut Vec<String>,
+    given: &toml::Value,
+    known: &toml::Value,
+    unknown: &mut Vec<String>,
+) {
+    match (given, known) {
+        (toml::Value::Table(given), toml::Value::Table(known)) => {
+            for (key, value) in given {
+                prefix.push(key.clone());
+                match known.get(key) {
+                    Some(known) => unknown_config_keys(prefix, value, known, unknown),
+                    None => unknown.push(prefix.join(".")),
+                }
+                let _ = prefix.pop();
+            }
+        }
+
+        (toml::Value::Array(given), toml::Value::Array(known)) => {
+            for (given, known) in given.iter().zip(known) {
+                unknown_config_keys(prefix, given, known, unknown);
+            }
+        }
+
+        _ => (),
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum Repository {
+    GitHub { user: String, repo: String },
+    GitLab { user: String, repo: String },
+    BitBucket { user: String, repo: String },
+    Custom { url: String },
+    None,
+}
+
+impl Default for Repository {
+    fn default() -> Self {
+        Repository::None
+    }
+}
+
+impl Repository {
+    pub fn url(&self) -> Option<String> {
+        match self {
+            Repository::GitHub { repo, user } => {
+                Some(format!("https://github.com/{}/{}", user, repo))
+            }
+            Repository::GitLab { repo, user } => {
+                Some(format!("https://gitlab.com/{}/{}", user, repo))
+            }
+            Repository::BitBucket { repo, user } => {
+                Some(format!("https://bitbucket.org/{}/{}", user, repo))
+            }
+            Repository::Custom { url } => Some(url.clone()),
+            Repository::None => None,
+        }
+    }
+
+    /// A link to a line of a file in the repository. Released versions are
+    /// expected to be tagged `v<version>`, unversioned projects link to master.
+    ///
+    pub fn source_url(&self, version: &Option<String>, path: &str, line: usize) -> Option<String> {
+        let tag = match version {
+            Some(version) => format!("v{}", version),
+            None => "master".to_string(),
+        };
+        match self {
+            Repository::GitHub { .. } => {
+                Some(format!("{}/blob/{}/{}#L{}", self.url()?, tag, path, line))
+            }
+            Repository::GitLab { .. } => {
+                Some(format!("{}/-/blob/{}/{}#L{}", self.url()?, tag, path, line))
+            }
+            Repository::BitBucket { .. } => Some(format!(
+                "{}/src/{}/{}#lines-{}",
+                self.url()?,
+                tag,
+                path,
+                line
+            )),
+            Repository::Custom { .. } | Repository::None => None,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq)]
+pub struct Link {
+    pub title: String,
+    pub href: String,
 }
 
 #[derive(Debug, PartialEq)]
@@ -33,6 +450,8 @@ pub struct Analysed {
     pub origin: ModuleOrigin,
     pub type_info: typ::Module,
     pub source_base_path: PathBuf,
+    pub path: PathBuf,
+    pub src: String,
     pub warnings: Vec<Warning>,
 }
 
@@ -42,7 +461,8 @@ pub struct OutputFile {
     pub path: PathBuf,
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize)]
+#[serde(rename_all = "lowercase")]
 pub enum ModuleOrigin {
     Src,
     Test,
@@ -67,14 +487,25 @@ pub struct Module {
     module: crate::ast::UntypedModule,
 }
 
-pub fn analysed(inputs: Vec<Input>) -> Result<Vec<Analysed>, Error> {
+/// The graph of imports between the given modules.
+///
+pub fn import_graph(inputs: Vec<Input>) -> Result<import_graph::ImportGraph, Error> {
+    Ok(SourceTree::new(inputs, &[])?.import_graph())
+}
+
+/// Type check the given modules in dependency order, failing if any import
+/// is forbidden by the import rules.
+///
+pub fn analysed(inputs: Vec<Input>, import_rules: &[ImportRule]) -> Result<Vec<Analysed>, Error> {
     let module_count = inputs.len();
-    let mut source_tree = SourceTree::new(inputs)?;
+    let mut source_tree = SourceTree::new(inputs, import_rules)?;
     let mut modules_type_infos = HashMap::new();
     let mut compiled_modules = Vec::with_capacity(module_count);
 
     struct Out {
         source_base_path: PathBuf,
+        path: PathBuf,
+        src: String,
         name_string: String,
         name: Vec<String>,
         origin: ModuleOrigin,
@@ -117,6 +548,8 @@ pub fn analysed(inputs: Vec<Input>) -> Result<Vec<Analysed>, Error> {
             name,
             name_string,
             source_base_path,
+            path,
+            src,
             origin,
             ast,
             warnings,
@@ -129,6 +562,8 @@ pub fn analysed(inputs: Vec<Input>) -> Result<Vec<Analysed>, Error> {
             let Out {
                 name,
                 source_base_path,
+                path,
+                src,
                 name_string,
                 origin,
                 ast,
@@ -138,6 +573,8 @@ pub fn analysed(inputs: Vec<Input>) -> Result<Vec<Analysed>, Error> {
                 ast,
                 name,
                 source_base_path,
+                path,
+                src,
                 origin,
                 type_info: modules_type_infos
                     .remove(&name_string)
@@ -148,22 +585,27 @@ pub fn analysed(inputs: Vec<Input>) -> Result<Vec<Analysed>, Error> {
         .collect())
 }
 
-pub fn generate_erlang(analysed: &[Analysed], files: &mut Vec<OutputFile>) {
+pub fn generate_erlang(analysed: &[Analysed], gen_dir_name: &PathBuf, files: &mut Vec<OutputFile>) {
     for Analysed {
         name,
         origin,
         source_base_path,
         ast,
+        src,
+        path,
         ..
     } in analysed
     {
-        let gen_dir = source_base_path
-            .parent()
-            .unwrap()
-            .join("gen")
-            .join(origin.dir_name());
+        let gen_dir = gen_dir(source_base_path, gen_dir_name, origin);
         let erl_module_name = name.join("@");
 
+        // The path of the source file relative to the root of its package
+        let src_path = path
+            .strip_prefix(source_base_path.parent().unwrap_or(source_base_path))
+            .unwrap_or(path)
+            .to_string_lossy()
+            .replace("\\", "/");
+
         for (name, text) in crate::erl::records(&ast).into_iter() {
             files.push(OutputFile {
                 path: gen_dir.join(format!("{}_{}.hrl", erl_module_name, name)),
@@ -173,15 +615,68 @@ pub fn generate_erlang(analysed: &[Analysed], files: &mut Vec<OutputFile>) {
 
         files.push(OutputFile {
             path: gen_dir.join(format!("{}.erl", erl_module_name)),
-            text: crate::erl::module(&ast),
+            text: crate::erl::module(&ast, &LineNumbers::new(src), &src_path),
         });
     }
 }
 
+/// The directory the Erlang for modules of the given origin is written to,
+/// relative to the root of the package they belong to.
+///
+fn gen_dir(source_base_path: &PathBuf, gen_dir_name: &PathBuf, origin: &ModuleOrigin) -> PathBuf {
+    source_base_path
+        .parent()
+        .unwrap_or_else(|| source_base_path.as_path())
+        .join(gen_dir_name)
+        .join(origin.dir_name())
+}
+
+impl Analysed {
+    /// The path of the Erlang module generated for this module.
+    ///
+    pub fn erl_path(&self, gen_dir_name: &PathBuf) -> PathBuf {
+        gen_dir(&self.source_base_path, gen_dir_name, &self.origin)
+            .join(format!("{}.erl", self.name.join("@")))
+    }
+}
+
+/// Generate the OTP application resource file for the project's own modules,
+/// both Gleam and hand-written Erlang, into the given directory.
+///
+pub fn generate_app_file(
+    config: &ProjectConfig,
+    analysed: &[Analysed],
+    erlang_files: &[PathBuf],
+    dir: &PathBuf,
+    files: &mut Vec<OutputFile>,
+) {
+    let mut modules: Vec<_> = analysed
+        .iter()
+        .filter(|module| module.origin == ModuleOrigin::Src)
+        .map(|module| module.name.join("@"))
+        .chain(
+            erlang_files
+                .iter()
+                .filter_map(|path| path.file_stem())
+                .map(|name| name.to_string_lossy().to_string()),
+        )
+        .collect();
+    modules.sort();
+
+    files.push(OutputFile {
+        path: dir.join(format!("{}.app", config.name)),
+        text: crate::erl::application(config, modules.as_slice()),
+    });
+}
+
+/// Whether a path within a source directory is a valid Gleam module name.
+/// Each part of the name starts with a lowercase letter or an underscore and
+/// contains only lowercase letters, numbers, and underscores.
+///
 fn is_gleam_path(path: &PathBuf, dir: &PathBuf) -> bool {
     use regex::Regex;
     lazy_static! {
-        static ref RE: Regex = Regex::new("^([a-z_]+(/|\\\\))*[a-z_]+\\.gleam$")
+        static ref RE: Regex = Regex::new("^([a-z_][a-z0-9_]*(/|\\\\))*[a-z_][a-z0-9_]*\\.gleam$")
             .gleam_expect("project::collect_source() RE regex");
     }
 
@@ -194,18 +689,33 @@ fn is_gleam_path(path: &PathBuf, dir: &PathBuf) -> bool {
 }
 
 pub fn gleam_files(dir: &PathBuf) -> impl Iterator<Item = PathBuf> + '_ {
+    files_with_extension(dir, "gleam")
+}
+
+/// Hand-written Erlang modules in a source directory, which are compiled
+/// along with the Erlang generated from Gleam.
+///
+pub fn erlang_files(dir: &PathBuf) -> impl Iterator<Item = PathBuf> + '_ {
+    files_with_extension(dir, "erl")
+}
+
+fn files_with_extension<'a>(
+    dir: &'a PathBuf,
+    extension: &'a str,
+) -> impl Iterator<Item = PathBuf> + 'a {
     walkdir::WalkDir::new(dir.clone())
         .into_iter()
         .filter_map(Result::ok)
         .filter(|e| e.file_type().is_file())
         .map(|d| d.path().to_path_buf())
-        .filter(move |d| is_gleam_path(d, &dir))
+        .filter(move |d| d.extension().and_then(|e| e.to_str()) == Some(extension))
 }
 
 pub fn collect_source(
     src_dir: PathBuf,
     origin: ModuleOrigin,
     srcs: &mut Vec<Input>,
+    warnings: &mut Vec<Warning>,
 ) -> Result<(), Error> {
     let src_dir = match src_dir.canonicalize() {
         Ok(d) => d,
@@ -213,6 +723,22 @@ pub fn collect_source(
     };
 
     for path in gleam_files(&src_dir) {
+        if !is_gleam_path(&path, &src_dir) {
+            let module = path
+                .strip_prefix(&src_dir)
+                .gleam_expect("project::collect_source(): strip_prefix")
+                .with_extension("")
+                .to_string_lossy()
+                .to_string();
+            // A badly named file in a dependency is not the project's to fix,
+            // so it is skipped rather than stopping the build
+            if origin == ModuleOrigin::Dependency {
+                warnings.push(Warning::InvalidModuleName { path, module });
+                continue;
+            }
+            return Err(Error::InvalidModuleName { path, module });
+        }
+
         let src = std::fs::read_to_string(&path).map_err(|err| Error::FileIO {
             action: FileIOAction::Read,
             kind: FileKind::File,
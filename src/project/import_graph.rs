@@ -0,0 +1,102 @@
+use super::ModuleOrigin;
+use itertools::Itertools;
+use serde::Serialize;
+use strum_macros::{Display, EnumString, EnumVariantNames};
+
+#[derive(Debug, PartialEq, Display, EnumString, EnumVariantNames)]
+#[strum(serialize_all = "kebab_case")]
+pub enum GraphFormat {
+    Dot,
+    Json,
+}
+
+/// The modules of a project and its dependencies, and the imports between
+/// them. Modules are sorted by package and then by name, with the modules of
+/// the project itself first.
+///
+#[derive(Debug, PartialEq, Serialize)]
+pub struct ImportGraph {
+    pub modules: Vec<GraphModule>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct GraphModule {
+    pub name: String,
+    pub origin: ModuleOrigin,
+    /// The package a dependency module belongs to.
+    pub package: Option<String>,
+    /// The modules imported by this module.
+    pub imports: Vec<String>,
+    /// The modules that import this module.
+    pub imported_by: Vec<String>,
+    /// The number of modules that import this module.
+    pub fan_in: usize,
+    /// The number of modules imported by this module.
+    pub fan_out: usize,
+}
+
+impl ImportGraph {
+    pub fn render(&self, format: &GraphFormat) -> String {
+        match format {
+            GraphFormat::Dot => self.to_dot(),
+            GraphFormat::Json => self.to_json(),
+        }
+    }
+
+    /// Render as a Graphviz DOT digraph, with an edge from each module to
+    /// the modules it imports. The modules of each dependency package are
+    /// grouped together in a cluster.
+    ///
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph imports {\n  node [shape=box, style=filled];\n");
+
+        let groups = self.modules.iter().group_by(|module| &module.package);
+        for (package, modules) in groups.into_iter() {
+            let (indent, close) = match package {
+                None => ("  ", ""),
+                Some(package) => {
+                    dot.push_str(&format!(
+                        "  subgraph \"cluster_{}\" {{\n    label = \"{}\";\n",
+                        package, package
+                    ));
+                    ("    ", "  }\n")
+                }
+            };
+            for module in modules {
+                dot.push_str(&format!(
+                    "{}\"{}\" [fillcolor={}, label=\"{}\\nin: {} out: {}\"];\n",
+                    indent,
+                    module.name,
+                    colour(&module.origin),
+                    module.name,
+                    module.fan_in,
+                    module.fan_out,
+                ));
+            }
+            dot.push_str(close);
+        }
+
+        for module in self.modules.iter() {
+            for import in module.imports.iter() {
+                dot.push_str(&format!("  \"{}\" -> \"{}\";\n", module.name, import));
+            }
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+
+    pub fn to_json(&self) -> String {
+        let mut json = serde_json::to_string_pretty(self).expect("import graph to_json");
+        json.push('\n');
+        json
+    }
+}
+
+fn colour(origin: &ModuleOrigin) -> &'static str {
+    match origin {
+        ModuleOrigin::Src => "lightblue",
+        ModuleOrigin::Test => "lightyellow",
+        ModuleOrigin::Dependency => "lightgrey",
+    }
+}
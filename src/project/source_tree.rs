@@ -1,6 +1,10 @@
-use super::{GleamExpect, Input, Module, ModuleOrigin};
-use crate::error::Error;
+use super::{
+    import_graph::{GraphModule, ImportGraph},
+    GleamExpect, ImportRule, Input, Module, ModuleOrigin,
+};
+use crate::error::{CycleImport, Error};
 use crate::parser::{self, Comment};
+use itertools::Itertools;
 use petgraph::{algo::Cycle, graph::NodeIndex, Direction};
 use std::collections::{HashMap, HashSet};
 
@@ -12,12 +16,12 @@ pub struct SourceTree {
 }
 
 impl SourceTree {
-    pub fn new(inputs: Vec<Input>) -> Result<Self, Error> {
+    pub fn new(inputs: Vec<Input>, import_rules: &[ImportRule]) -> Result<Self, Error> {
         let mut graph: SourceTree = Default::default();
         for input in inputs.into_iter() {
             graph.insert(input)?;
         }
-        graph.calculate_dependencies()?;
+        graph.calculate_dependencies(import_rules)?;
         Ok(graph)
     }
 
@@ -33,21 +37,91 @@ impl SourceTree {
         Ok(iter)
     }
 
-    fn import_cycle(&mut self, cycle: Cycle<NodeIndex>) -> Error {
+    pub fn import_graph(&self) -> ImportGraph {
+        let names = |indexes: petgraph::graph::Neighbors<'_, ()>| {
+            indexes
+                .map(|index| self.graph[index].clone())
+                .sorted()
+                .collect::<Vec<_>>()
+        };
+
+        let modules = self
+            .indexes
+            .iter()
+            .map(|(name, index)| {
+                let module = self
+                    .modules
+                    .get(index)
+                    .gleam_expect("SourceTree.import_graph(): Unable to find module for index");
+                let package = match module.origin {
+                    ModuleOrigin::Dependency => module
+                        .source_base_path
+                        .parent()
+                        .and_then(|path| path.file_name())
+                        .map(|name| name.to_string_lossy().to_string()),
+                    ModuleOrigin::Src | ModuleOrigin::Test => None,
+                };
+                // Edges go from a dependency to the modules that import it
+                let imports = names(self.graph.neighbors_directed(*index, Direction::Incoming));
+                let imported_by = names(self.graph.neighbors_directed(*index, Direction::Outgoing));
+                GraphModule {
+                    name: name.clone(),
+                    origin: module.origin.clone(),
+                    package,
+                    fan_in: imported_by.len(),
+                    fan_out: imports.len(),
+                    imports,
+                    imported_by,
+                }
+            })
+            .sorted_by(|a, b| (&a.package, &a.name).cmp(&(&b.package, &b.name)))
+            .collect();
+
+        ImportGraph { modules }
+    }
+
+    /// The imports forming a cycle, each module in the cycle importing the
+    /// next, and the last importing the first.
+    ///
+    fn import_cycle(&self, cycle: Cycle<NodeIndex>) -> Error {
         let origin = cycle.node_id();
         let mut path = vec![];
         self.find_cycle(origin, origin, &mut path, &mut HashSet::new());
-        let modules: Vec<_> = path
+        // Start from the first module by name so the error is the same
+        // whichever module the cycle was detected at
+        if let Some((first, _)) = path
             .iter()
-            .map(|index| {
-                self.modules
-                    .remove(index)
-                    .gleam_expect("SourceTree.import_cycle(): cannot find module for index")
+            .enumerate()
+            .min_by_key(|(_, index)| &self.graph[**index])
+        {
+            path.rotate_left(first);
+        }
+        let imports = path
+            .iter()
+            .zip(path.iter().cycle().skip(1))
+            .map(|(index, imported_index)| {
+                let module = self
+                    .modules
+                    .get(index)
+                    .gleam_expect("SourceTree.import_cycle(): cannot find module for index");
+                let import = self.graph[*imported_index].clone();
+                let location = module
                     .module
-                    .name
+                    .dependencies()
+                    .into_iter()
+                    .find(|(dep, _)| dep == &import)
+                    .map(|(_, location)| location)
+                    .gleam_expect("SourceTree.import_cycle(): cannot find import in module");
+                CycleImport {
+                    module: module.module.name_string(),
+                    import,
+                    path: module.path.clone(),
+                    src: module.src.clone(),
+                    location,
+                }
             })
             .collect();
-        Error::ImportCycle { modules }
+        Error::ImportCycle { imports }
     }
 
     fn find_cycle(
@@ -74,7 +148,7 @@ impl SourceTree {
         false
     }
 
-    fn calculate_dependencies(&mut self) -> Result<(), Error> {
+    fn calculate_dependencies(&mut self, import_rules: &[ImportRule]) -> Result<(), Error> {
         for module in self.modules.values() {
             let module_name = module.module.name_string();
             let src = module.src.clone();
@@ -118,6 +192,23 @@ impl SourceTree {
                     });
                 }
 
+                // The import rules are for the project's own modules
+                if module.origin != ModuleOrigin::Dependency {
+                    if let Some(rule) = import_rules
+                        .iter()
+                        .find(|rule| rule.forbids(&module_name, &dep))
+                    {
+                        return Err(Error::ForbiddenImport {
+                            path: path.clone(),
+                            src: src.clone(),
+                            location,
+                            module: module_name,
+                            import: dep,
+                            rule: rule.clone(),
+                        });
+                    }
+                }
+
                 self.graph
                     .add_edge(dep_index.clone(), module_index.clone(), ());
             }
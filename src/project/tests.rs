@@ -1,4 +1,5 @@
 use super::*;
+use crate::error::CycleImport;
 
 #[test]
 fn compile_test() {
@@ -148,7 +149,7 @@ fn compile_test() {
                 OutputFile {
                     path: PathBuf::from("/gen/src/two.erl"),
                     text: "-module(two).\n-compile(no_auto_import).\n\n-export([unbox/1]).\n
-unbox(X) ->\n    {box, I} = X,\n    I.\n"
+-file(\"src/two.gleam\", 1).\nunbox(X) ->\n    {box, I} = X,\n    I.\n"
                         .to_string(),
                 },
             ]),
@@ -176,7 +177,7 @@ unbox(X) ->\n    {box, I} = X,\n    I.\n"
                 OutputFile {
                     path: PathBuf::from("/gen/src/two.erl"),
                     text: "-module(two).\n-compile(no_auto_import).\n\n-export([box/1]).\n
-box(X) ->\n    {box, X}.\n"
+-file(\"src/two.gleam\", 1).\nbox(X) ->\n    {box, X}.\n"
                         .to_string(),
                 },
             ]),
@@ -216,7 +217,7 @@ box(X) ->\n    {box, X}.\n"
                 OutputFile {
                     path: PathBuf::from("/gen/src/two.erl"),
                     text: "-module(two).\n-compile(no_auto_import).\n\n-export([box/0]).\n
-box() ->\n    box.\n"
+-file(\"src/two.gleam\", 1).\nbox() ->\n    box.\n"
                         .to_string(),
                 },
             ]),
@@ -240,14 +241,14 @@ box() ->\n    box.\n"
                 OutputFile {
                     path: PathBuf::from("/gen/src/one.erl"),
                     text: "-module(one).\n-compile(no_auto_import).\n\n-export([go/0]).\n
-go() ->
+-file(\"src/one.gleam\", 1).\ngo() ->
     1.\n"
                         .to_string(),
                 },
                 OutputFile {
                     path: PathBuf::from("/gen/src/two.erl"),
                     text: "-module(two).\n-compile(no_auto_import).\n\n-export([call/0]).\n
-call() ->
+-file(\"src/two.gleam\", 1).\ncall() ->
     one:go().\n"
                         .to_string(),
                 },
@@ -276,7 +277,7 @@ call() ->
                 OutputFile {
                     path: PathBuf::from("/gen/src/two.erl"),
                     text: "-module(two).\n-compile(no_auto_import).\n\n-export([go/1]).
-\ngo(X) ->\n    {box, Y} = X,\n    Y.\n"
+\n-file(\"src/two.gleam\", 2).\ngo(X) ->\n    {box, Y} = X,\n    Y.\n"
                         .to_string(),
                 },
             ]),
@@ -305,7 +306,7 @@ call() ->
                 OutputFile {
                     path: PathBuf::from("/gen/src/two.erl"),
                     text: "-module(two).\n-compile(no_auto_import).\n\n-export([go/1]).
-\ngo(X) ->\n    {box, Y} = X,\n    Y.\n"
+\n-file(\"src/two.gleam\", 2).\ngo(X) ->\n    {box, Y} = X,\n    Y.\n"
                         .to_string(),
                 },
             ]),
@@ -332,14 +333,14 @@ call() ->
                 OutputFile {
                     path: PathBuf::from("/gen/src/nested@one.erl"),
                     text: "-module(nested@one).\n-compile(no_auto_import).\n\n-export([go/0]).\n
-go() ->\n    1.\n"
+-file(\"src/nested/one.gleam\", 1).\ngo() ->\n    1.\n"
                         .to_string(),
                 },
                 OutputFile {
                     path: PathBuf::from("/gen/src/two.erl"),
                     text: "-module(two).\n-compile(no_auto_import).\n\n-export([go/0, thing/0]).\n
-go() ->\n    nested@one:go().\n
-thing() ->\n    thing:new().\n"
+-file(\"src/two.gleam\", 2).\ngo() ->\n    nested@one:go().\n
+-file(\"src/two.gleam\", 3).\nthing() ->\n    thing:new().\n"
                         .to_string(),
                 },
             ]),
@@ -395,8 +396,8 @@ thing() ->\n    thing:new().\n"
                 OutputFile {
                     path: PathBuf::from("/gen/src/two.erl"),
                     text: "-module(two).\n-compile(no_auto_import).\n
-make() ->\n    {point, 1, 4}.\n
-x(P) ->\n    {point, X, _} = P,\n    X.\n"
+-file(\"src/two.gleam\", 2).\nmake() ->\n    {point, 1, 4}.\n
+-file(\"src/two.gleam\", 3).\nx(P) ->\n    {point, X, _} = P,\n    X.\n"
                         .to_string(),
                 },
             ]),
@@ -422,13 +423,13 @@ x(P) ->\n    {point, X, _} = P,\n    X.\n"
                 OutputFile {
                     path: PathBuf::from("/gen/src/one.erl"),
                     text: "-module(one).\n-compile(no_auto_import).\n\n-export([\'div\'/2]).\n
-'div'(X, Y) ->\n    X div Y.\n"
+-file(\"src/one.gleam\", 1).\n'div'(X, Y) ->\n    X div Y.\n"
                         .to_string(),
                 },
                 OutputFile {
                     path: PathBuf::from("/gen/src/two.erl"),
                     text: "-module(two).\n-compile(no_auto_import).\n
-run() ->\n    one:'div'(2, one:'div'(2, 4)).\n"
+-file(\"src/two.gleam\", 2).\nrun() ->\n    one:'div'(2, one:'div'(2, 4)).\n"
                         .to_string(),
                 },
             ]),
@@ -458,7 +459,7 @@ run() ->\n    one:'div'(2, one:'div'(2, 4)).\n"
                 OutputFile {
                     path: PathBuf::from("/gen/src/two.erl"),
                     text: "-module(two).\n-compile(no_auto_import).\n
-make() ->\n    empty.\n"
+-file(\"src/two.gleam\", 2).\nmake() ->\n    empty.\n"
                         .to_string(),
                 },
             ]),
@@ -482,13 +483,13 @@ make() ->\n    empty.\n"
                 OutputFile {
                     path: PathBuf::from("/gen/src/one.erl"),
                     text: "-module(one).\n-compile(no_auto_import).\n\n-export([id/1]).\n
-id(X) ->\n    X.\n"
+-file(\"src/one.gleam\", 1).\nid(X) ->\n    X.\n"
                         .to_string(),
                 },
                 OutputFile {
                     path: PathBuf::from("/gen/src/two.erl"),
                     text: "-module(two).\n-compile(no_auto_import).\n
-make() ->
+-file(\"src/two.gleam\", 1).\nmake() ->
     one:id(empty).\n"
                         .to_string(),
                 },
@@ -515,13 +516,13 @@ make() ->
                     path: PathBuf::from("/gen/src/one.erl"),
                     text: "-module(one).\n-compile(no_auto_import).\n
 -export([id/1]).\n
-id(X) ->\n    X.\n"
+-file(\"src/one.gleam\", 1).\nid(X) ->\n    X.\n"
                         .to_string(),
                 },
                 OutputFile {
                     path: PathBuf::from("/gen/src/two.erl"),
                     text: "-module(two).\n-compile(no_auto_import).\n
-make() ->\n    one:id(empty).\n"
+-file(\"src/two.gleam\", 1).\nmake() ->\n    one:id(empty).\n"
                         .to_string(),
                 },
             ]),
@@ -546,13 +547,13 @@ make() ->\n    one:id(empty).\n"
                     path: PathBuf::from("/gen/src/one.erl"),
                     text: "-module(one).\n-compile(no_auto_import).\n
 -export(['receive'/0]).\n
-'receive'() ->\n    1.\n"
+-file(\"src/one.gleam\", 1).\n'receive'() ->\n    1.\n"
                         .to_string(),
                 },
                 OutputFile {
                     path: PathBuf::from("/gen/src/two.erl"),
                     text: "-module(two).\n-compile(no_auto_import).\n
-funky() ->
+-file(\"src/two.gleam\", 1).\nfunky() ->
     fun one:'receive'/0.\n"
                         .to_string(),
                 },
@@ -578,12 +579,12 @@ funky() ->
                     path: PathBuf::from("/gen/src/one.erl"),
                     text: "-module(one).\n-compile(no_auto_import).\n
 -export(['receive'/0]).\n
-'receive'() ->\n    1.\n"
+-file(\"src/one.gleam\", 1).\n'receive'() ->\n    1.\n"
                         .to_string(),
                 },
                 OutputFile {
                     path: PathBuf::from("/gen/src/two.erl"),
-                    text: "-module(two).\n-compile(no_auto_import).\n\nfunky() ->
+                    text: "-module(two).\n-compile(no_auto_import).\n\n-file(\"src/two.gleam\", 1).\nfunky() ->
     fun one:'receive'/0.\n"
                         .to_string(),
                 },
@@ -610,12 +611,12 @@ funky() ->
                     path: PathBuf::from("/gen/src/one.erl"),
                     text: "-module(one).\n-compile(no_auto_import).\n
 -export(['receive'/1]).\n
-'receive'(X) ->\n    X.\n"
+-file(\"src/one.gleam\", 1).\n'receive'(X) ->\n    X.\n"
                         .to_string(),
                 },
                 OutputFile {
                     path: PathBuf::from("/gen/src/two.erl"),
-                    text: "-module(two).\n-compile(no_auto_import).\n\nfunky() ->
+                    text: "-module(two).\n-compile(no_auto_import).\n\n-file(\"src/two.gleam\", 1).\nfunky() ->
     one:'receive'(1).\n"
                         .to_string(),
                 },
@@ -655,10 +656,10 @@ pub fn get_name(person: Person) { person.name }"
                     text: "-module(two).\n-compile(no_auto_import).\n
 -export([get_age/1, get_name/1]).
 
-get_age(Person) ->
+-file(\"src/two.gleam\", 2).\nget_age(Person) ->
     erlang:element(3, Person).
 
-get_name(Person) ->
+-file(\"src/two.gleam\", 3).\nget_name(Person) ->
     erlang:element(2, Person).\n"
                         .to_string(),
                 },
@@ -730,14 +731,668 @@ type Two = Person"
                 },
             ]),
         },
+        Case {
+            input: vec![
+                Input {
+                    origin: ModuleOrigin::Src,
+                    path: PathBuf::from("/src/two.gleam"),
+                    source_base_path: PathBuf::from("/src"),
+                    src: "import three".to_string(),
+                },
+                Input {
+                    origin: ModuleOrigin::Src,
+                    path: PathBuf::from("/src/one.gleam"),
+                    source_base_path: PathBuf::from("/src"),
+                    src: "import two".to_string(),
+                },
+                Input {
+                    origin: ModuleOrigin::Src,
+                    path: PathBuf::from("/src/three.gleam"),
+                    source_base_path: PathBuf::from("/src"),
+                    src: "import four\nimport one".to_string(),
+                },
+                Input {
+                    origin: ModuleOrigin::Src,
+                    path: PathBuf::from("/src/four.gleam"),
+                    source_base_path: PathBuf::from("/src"),
+                    src: "".to_string(),
+                },
+            ],
+            expected: Err(Error::ImportCycle {
+                imports: vec![
+                    CycleImport {
+                        module: "one".to_string(),
+                        import: "two".to_string(),
+                        path: PathBuf::from("/src/one.gleam"),
+                        src: "import two".to_string(),
+                        location: crate::ast::SrcSpan { start: 7, end: 10 },
+                    },
+                    CycleImport {
+                        module: "two".to_string(),
+                        import: "three".to_string(),
+                        path: PathBuf::from("/src/two.gleam"),
+                        src: "import three".to_string(),
+                        location: crate::ast::SrcSpan { start: 7, end: 12 },
+                    },
+                    CycleImport {
+                        module: "three".to_string(),
+                        import: "one".to_string(),
+                        path: PathBuf::from("/src/three.gleam"),
+                        src: "import four\nimport one".to_string(),
+                        location: crate::ast::SrcSpan { start: 19, end: 22 },
+                    },
+                ],
+            }),
+        },
     ];
 
     for Case { input, expected } in cases.into_iter() {
-        let actual = analysed(input).map(|analysed| {
+        let actual = analysed(input, &[]).map(|analysed| {
             let mut output = Vec::with_capacity(analysed.len());
-            crate::project::generate_erlang(analysed.as_slice(), &mut output);
+            crate::project::generate_erlang(
+                analysed.as_slice(),
+                &PathBuf::from("gen"),
+                &mut output,
+            );
             output
         });
         assert_eq!(expected, actual);
     }
 }
+
+#[test]
+fn forbidden_imports_test() {
+    let rules = vec![ImportRule {
+        from: "app/domain".to_string(),
+        to: "app/web".to_string(),
+        reason: None,
+    }];
+    let input = |origin: ModuleOrigin, name: &str, src: &str| Input {
+        origin,
+        path: PathBuf::from(format!("/src/{}.gleam", name)),
+        source_base_path: PathBuf::from("/src"),
+        src: src.to_string(),
+    };
+
+    // Modules under the `from` path may not import modules under `to`
+    let result = analysed(
+        vec![
+            input(ModuleOrigin::Src, "app/web/router", ""),
+            input(
+                ModuleOrigin::Src,
+                "app/domain/user",
+                "import app/web/router",
+            ),
+        ],
+        &rules,
+    );
+    assert_eq!(
+        Err(Error::ForbiddenImport {
+            path: PathBuf::from("/src/app/domain/user.gleam"),
+            src: "import app/web/router".to_string(),
+            location: crate::ast::SrcSpan { start: 7, end: 21 },
+            module: "app/domain/user".to_string(),
+            import: "app/web/router".to_string(),
+            rule: rules[0].clone(),
+        }),
+        result.map(|_| ())
+    );
+
+    // The rule matches whole path segments only, and applies in one direction
+    for (importer, imported) in &[
+        ("app/domainx", "app/web"),
+        ("app/domain", "app/webx"),
+        ("app/web", "app/domain"),
+    ] {
+        let result = analysed(
+            vec![
+                input(ModuleOrigin::Src, imported, ""),
+                input(ModuleOrigin::Src, importer, &format!("import {}", imported)),
+            ],
+            &rules,
+        );
+        assert!(result.is_ok());
+    }
+
+    // Dependencies are not subject to the project's rules
+    let result = analysed(
+        vec![
+            input(ModuleOrigin::Dependency, "app/web", ""),
+            input(ModuleOrigin::Dependency, "app/domain", "import app/web"),
+        ],
+        &rules,
+    );
+    assert!(result.is_ok());
+}
+
+#[test]
+fn is_gleam_path_test() {
+    let dir = PathBuf::from("/src");
+    let valid = |path: &str| is_gleam_path(&PathBuf::from(path), &dir);
+
+    assert!(valid("/src/one.gleam"));
+    assert!(valid("/src/one_two.gleam"));
+    assert!(valid("/src/http2.gleam"));
+    assert!(valid("/src/v1/api.gleam"));
+    assert!(valid("/src/nested/one/two.gleam"));
+    assert!(valid("/src/_one.gleam"));
+    assert!(valid("/src/one/_two.gleam"));
+
+    assert!(!valid("/src/One.gleam"));
+    assert!(!valid("/src/1one.gleam"));
+    assert!(!valid("/src/one-two.gleam"));
+    assert!(!valid("/src/1/one.gleam"));
+    assert!(!valid("/src/one.erl"));
+}
+
+#[test]
+fn collect_source_invalid_module_name_test() {
+    let dir = std::env::temp_dir().join("gleam_collect_source_invalid_module_name_test");
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("one.gleam"), "pub fn one() { 1 }").unwrap();
+    std::fs::write(dir.join("1two.gleam"), "pub fn two() { 2 }").unwrap();
+    let path = dir.canonicalize().unwrap().join("1two.gleam");
+
+    // Files in dependencies with invalid names are skipped with a warning
+    let mut srcs = vec![];
+    let mut warnings = vec![];
+    collect_source(
+        dir.clone(),
+        ModuleOrigin::Dependency,
+        &mut srcs,
+        &mut warnings,
+    )
+    .expect("should collect dependency source");
+    assert_eq!(
+        vec![dir.canonicalize().unwrap().join("one.gleam")],
+        srcs.into_iter().map(|input| input.path).collect::<Vec<_>>()
+    );
+    assert_eq!(
+        vec![Warning::InvalidModuleName {
+            path: path.clone(),
+            module: "1two".to_string(),
+        }],
+        warnings
+    );
+
+    // In the project itself they are an error
+    let mut warnings = vec![];
+    assert_eq!(
+        Err(Error::InvalidModuleName {
+            path,
+            module: "1two".to_string(),
+        }),
+        collect_source(dir.clone(), ModuleOrigin::Src, &mut vec![], &mut warnings)
+    );
+    assert!(warnings.is_empty());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn deprecation_warnings_test() {
+    let one = "@deprecated(\"use new_go\")
+pub fn go() { 1 }
+
+@deprecated(\"use Thing\")
+pub type Box { Box(Int) }
+
+pub fn new_go() { go() }";
+
+    let warnings = |src: &str| {
+        let inputs = vec![
+            Input {
+                origin: ModuleOrigin::Src,
+                path: PathBuf::from("/src/one.gleam"),
+                source_base_path: PathBuf::from("/src"),
+                src: one.to_string(),
+            },
+            Input {
+                origin: ModuleOrigin::Src,
+                path: PathBuf::from("/src/two.gleam"),
+                source_base_path: PathBuf::from("/src"),
+                src: src.to_string(),
+            },
+        ];
+        let mut analysed = analysed(inputs, &[]).expect("should successfully analyse");
+        // Uses within the defining module do not emit warnings
+        assert_eq!(vec![] as Vec<Warning>, analysed[0].warnings);
+        analysed
+            .remove(1)
+            .warnings
+            .into_iter()
+            .map(|warning| match warning {
+                Warning::Type { warning, .. } => warning,
+                _ => panic!("expected a type warning"),
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let deprecated = |start, end, name: &str, message: &str| typ::Warning::DeprecatedItem {
+        location: crate::ast::SrcSpan { start, end },
+        name: name.to_string(),
+        message: message.to_string(),
+    };
+
+    assert_eq!(
+        vec![deprecated(29, 32, "go", "use new_go")],
+        warnings("import one pub fn run() { one.go() }")
+    );
+    assert_eq!(
+        vec![deprecated(31, 33, "go", "use new_go")],
+        warnings("import one.{go} pub fn run() { go() }")
+    );
+    assert_eq!(
+        vec![deprecated(25, 32, "Box", "use Thing")],
+        warnings("import one pub fn run(x: one.Box) { x }")
+    );
+    assert_eq!(
+        vec![deprecated(29, 33, "Box", "use Thing")],
+        warnings("import one pub fn run() { one.Box(1) }")
+    );
+    assert_eq!(
+        vec![deprecated(31, 41, "Box", "use Thing")],
+        warnings("import one pub fn run(x) { let one.Box(y) = x y }")
+    );
+    assert_eq!(
+        vec![] as Vec<typ::Warning>,
+        warnings("import one pub fn run() { one.new_go() }")
+    );
+}
+
+#[test]
+fn import_graph_test() {
+    let input = |origin, base: &str, name: &str, src: &str| Input {
+        origin,
+        source_base_path: PathBuf::from(base),
+        path: PathBuf::from(format!("{}/{}.gleam", base, name)),
+        src: src.to_string(),
+    };
+    let graph = import_graph(vec![
+        input(ModuleOrigin::Test, "/test", "one_test", "import one"),
+        input(
+            ModuleOrigin::Src,
+            "/src",
+            "one",
+            "import two import lib/thing",
+        ),
+        input(ModuleOrigin::Src, "/src", "two", "import lib/thing"),
+        input(
+            ModuleOrigin::Dependency,
+            "/_checkouts/lib/src",
+            "lib/thing",
+            "",
+        ),
+    ])
+    .unwrap();
+
+    let module =
+        |name: &str, origin, package: Option<&str>, imports: &[&str], imported_by: &[&str]| {
+            import_graph::GraphModule {
+                name: name.to_string(),
+                origin,
+                package: package.map(|p| p.to_string()),
+                imports: imports.iter().map(|s| s.to_string()).collect(),
+                imported_by: imported_by.iter().map(|s| s.to_string()).collect(),
+                fan_in: imported_by.len(),
+                fan_out: imports.len(),
+            }
+        };
+    assert_eq!(
+        import_graph::ImportGraph {
+            modules: vec![
+                module(
+                    "one",
+                    ModuleOrigin::Src,
+                    None,
+                    &["lib/thing", "two"],
+                    &["one_test"]
+                ),
+                module("one_test", ModuleOrigin::Test, None, &["one"], &[]),
+                module("two", ModuleOrigin::Src, None, &["lib/thing"], &["one"]),
+                module(
+                    "lib/thing",
+                    ModuleOrigin::Dependency,
+                    Some("lib"),
+                    &[],
+                    &["one", "two"]
+                ),
+            ]
+        },
+        graph
+    );
+
+    assert_eq!(
+        r#"digraph imports {
+  node [shape=box, style=filled];
+  "one" [fillcolor=lightblue, label="one\nin: 1 out: 2"];
+  "one_test" [fillcolor=lightyellow, label="one_test\nin: 0 out: 1"];
+  "two" [fillcolor=lightblue, label="two\nin: 1 out: 1"];
+  subgraph "cluster_lib" {
+    label = "lib";
+    "lib/thing" [fillcolor=lightgrey, label="lib/thing\nin: 2 out: 0"];
+  }
+  "one" -> "lib/thing";
+  "one" -> "two";
+  "one_test" -> "one";
+  "two" -> "lib/thing";
+}
+"#,
+        graph.to_dot()
+    );
+
+    assert!(graph.to_json().contains(
+        r#""name": "lib/thing",
+      "origin": "dependency",
+      "package": "lib","#
+    ));
+}
+
+#[test]
+fn project_config_test() {
+    let config: ProjectConfig = toml::from_str(r#"name = "thing""#).unwrap();
+    assert_eq!(
+        config,
+        ProjectConfig {
+            name: "thing".to_string(),
+            ..Default::default()
+        }
+    );
+
+    let config: ProjectConfig = toml::from_str(
+        r#"
+name = "thing"
+version = "1.2.0"
+description = "A thing"
+repository = { type = "github", user = "gleam-lang", repo = "thing" }
+links = [{ title = "Home page", href = "https://example.com" }]
+"#,
+    )
+    .unwrap();
+    assert_eq!(config.version, Some("1.2.0".to_string()));
+    assert_eq!(config.description, Some("A thing".to_string()));
+    assert_eq!(
+        config.repository.url(),
+        Some("https://github.com/gleam-lang/thing".to_string())
+    );
+    assert_eq!(
+        config.links,
+        vec![Link {
+            title: "Home page".to_string(),
+            href: "https://example.com".to_string(),
+        }]
+    );
+    assert_eq!(
+        config
+            .repository
+            .source_url(&config.version, "src/thing.gleam", 12),
+        Some("https://github.com/gleam-lang/thing/blob/v1.2.0/src/thing.gleam#L12".to_string())
+    );
+    let bitbucket = Repository::BitBucket {
+        user: "gleam-lang".to_string(),
+        repo: "thing".to_string(),
+    };
+    assert_eq!(
+        bitbucket.url(),
+        Some("https://bitbucket.org/gleam-lang/thing".to_string())
+    );
+    assert_eq!(
+        bitbucket.source_url(&config.version, "src/thing.gleam", 12),
+        Some(
+            "https://bitbucket.org/gleam-lang/thing/src/v1.2.0/src/thing.gleam#lines-12"
+                .to_string()
+        )
+    );
+    assert_eq!(
+        Repository::None.source_url(&config.version, "src/thing.gleam", 12),
+        None
+    );
+}
+
+#[test]
+fn parse_config_test() {
+    let path = PathBuf::from("gleam.toml");
+    macro_rules! assert_config {
+        ($src:expr, $config:expr, $warnings:expr $(,)?) => {
+            assert_eq!(
+                Ok(($config, $warnings)),
+                parse_config(path.clone(), $src.to_string()),
+            );
+        };
+    }
+
+    macro_rules! assert_error {
+        ($src:expr, $location:expr, $error:expr $(,)?) => {
+            assert_eq!(
+                Err(Error::InvalidProjectConfig {
+                    path: path.clone(),
+                    src: $src.to_string(),
+                    location: $location,
+                    error: $error.to_string(),
+                }),
+                parse_config(path.clone(), $src.to_string()),
+            );
+        };
+    }
+
+    assert_config!(
+        r#"name = "thing""#,
+        ProjectConfig {
+            name: "thing".to_string(),
+            ..Default::default()
+        },
+        vec![],
+    );
+
+    assert_config!(
+        r#"name = "thing"
+licences = ["Apache-2.0"]
+targets = ["erlang"]
+
+[dependencies]
+gleam_stdlib = "~> 0.8"
+local = { path = "../local" }
+
+[dev-dependencies]
+gleam_test = "~> 1.0"
+
+[erlang]
+application_start_module = "thing_app"
+extra_applications = ["inets"]
+
+[docs]
+include_path_dependencies = true
+
+[format]
+line_width = 100
+sort_imports = true
+
+[[forbidden_imports]]
+from = "thing/domain"
+to = "thing/web"
+reason = "The domain must not depend on the web layer."
+"#,
+        ProjectConfig {
+            name: "thing".to_string(),
+            licences: vec!["Apache-2.0".to_string()],
+            targets: vec![Target::Erlang],
+            dependencies: [
+                (
+                    "gleam_stdlib".to_string(),
+                    Dependency::Hex("~> 0.8".to_string())
+                ),
+                (
+                    "local".to_string(),
+                    Dependency::Path {
+                        path: PathBuf::from("../local")
+                    }
+                ),
+            ]
+            .iter()
+            .cloned()
+            .collect(),
+            dev_dependencies: [(
+                "gleam_test".to_string(),
+                Dependency::Hex("~> 1.0".to_string())
+            )]
+            .iter()
+            .cloned()
+            .collect(),
+            erlang: ErlangConfig {
+                application_start_module: Some("thing_app".to_string()),
+                extra_applications: vec!["inets".to_string()],
+            },
+            docs: DocsConfig {
+                include_path_dependencies: true,
+            },
+            format: FormatConfig {
+                line_width: 100,
+                sort_imports: true,
+            },
+            forbidden_imports: vec![ImportRule {
+                from: "thing/domain".to_string(),
+                to: "thing/web".to_string(),
+                reason: Some("The domain must not depend on the web layer.".to_string()),
+            }],
+            ..Default::default()
+        },
+        vec![],
+    );
+
+    assert_config!(
+        r#"name = "thing"
+nmae = "thing"
+
+[docs]
+include_path_dependencies = true
+colour = "blue"
+
+[[links]]
+title = "Home"
+href = "https://example.com"
+hreff = "https://example.com"
+"#,
+        ProjectConfig {
+            name: "thing".to_string(),
+            links: vec![Link {
+                title: "Home".to_string(),
+                href: "https://example.com".to_string(),
+            }],
+            docs: DocsConfig {
+                include_path_dependencies: true,
+            },
+            ..Default::default()
+        },
+        vec![
+            Warning::UnknownConfigKey {
+                path: path.clone(),
+                key: "docs.colour".to_string(),
+            },
+            Warning::UnknownConfigKey {
+                path: path.clone(),
+                key: "links.hreff".to_string(),
+            },
+            Warning::UnknownConfigKey {
+                path: path.clone(),
+                key: "nmae".to_string(),
+            },
+        ],
+    );
+
+    assert_error!(
+        r#"version = "1.0.0""#,
+        Some(crate::ast::SrcSpan { start: 0, end: 1 }),
+        "missing field `name`",
+    );
+
+    assert_error!(
+        r#"name = "thing"
+targets = ["javascript"]
+"#,
+        Some(crate::ast::SrcSpan { start: 15, end: 22 }),
+        "unknown variant `javascript`, expected `erlang` for key `targets`",
+    );
+
+    assert_error!(
+        r#"name = "thing"
+[docs]
+include_path_dependencies = "yes"
+"#,
+        Some(crate::ast::SrcSpan { start: 22, end: 47 }),
+        "invalid type: string \"yes\", expected a boolean for key `docs.include_path_dependencies`",
+    );
+
+    assert_error!(
+        r#"name = "thing
+"#,
+        Some(crate::ast::SrcSpan { start: 13, end: 14 }),
+        "newline in string found",
+    );
+}
+
+#[test]
+fn profile_test() {
+    let (config, warnings) = parse_config(
+        PathBuf::from("gleam.toml"),
+        r#"name = "thing"
+
+[profiles.prod]
+warnings_as_errors = true
+
+[profiles.ci]
+include_tests = true
+build_dir = "_build/test"
+"#
+        .to_string(),
+    )
+    .unwrap();
+    assert_eq!(warnings, vec![]);
+
+    assert_eq!(
+        config.profile("dev"),
+        Ok(BuildProfile {
+            name: "dev".to_string(),
+            include_tests: true,
+            warnings_as_errors: false,
+            debug_info: true,
+            gen_dir: PathBuf::from("gen"),
+            docs_dir: PathBuf::from("docs"),
+            build_dir: PathBuf::from("_build").join("dev"),
+        })
+    );
+
+    assert_eq!(
+        config.profile("prod"),
+        Ok(BuildProfile {
+            name: "prod".to_string(),
+            include_tests: false,
+            warnings_as_errors: true,
+            debug_info: false,
+            gen_dir: PathBuf::from("gen"),
+            docs_dir: PathBuf::from("docs"),
+            build_dir: PathBuf::from("_build").join("prod"),
+        })
+    );
+
+    assert_eq!(
+        config.profile("ci"),
+        Ok(BuildProfile {
+            name: "ci".to_string(),
+            include_tests: true,
+            warnings_as_errors: false,
+            debug_info: false,
+            gen_dir: PathBuf::from("gen"),
+            docs_dir: PathBuf::from("docs"),
+            build_dir: PathBuf::from("_build/test"),
+        })
+    );
+
+    assert_eq!(
+        config.profile("staging"),
+        Err(Error::UnknownProfile {
+            name: "staging".to_string(),
+            profiles: vec!["ci".to_string(), "dev".to_string(), "prod".to_string()],
+        })
+    );
+}
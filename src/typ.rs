@@ -1,3 +1,4 @@
+mod call_graph;
 pub mod pretty;
 #[cfg(test)]
 mod tests;
@@ -197,6 +198,16 @@ impl FieldMap {
         }
     }
 
+    /// The label of each argument by position, if it has one.
+    ///
+    pub fn labels(&self) -> Vec<Option<String>> {
+        let mut labels = vec![None; self.arity];
+        for (label, index) in self.fields.iter() {
+            labels[*index] = Some(label.clone());
+        }
+        labels
+    }
+
     /// Reorder an argument list so that labelled fields supplied out-of-order are in the correct
     /// order.
     ///
@@ -363,6 +374,7 @@ impl<'a, 'b> Env<'a, 'b> {
                 origin: Default::default(),
                 module: vec![],
                 public: true,
+                deprecation: None,
             },
         )
         .gleam_expect("prelude inserting Int type");
@@ -393,6 +405,7 @@ impl<'a, 'b> Env<'a, 'b> {
                 typ: bool(),
                 module: vec![],
                 public: true,
+                deprecation: None,
             },
         )
         .gleam_expect("prelude inserting Bool type");
@@ -406,6 +419,7 @@ impl<'a, 'b> Env<'a, 'b> {
                 typ: list(list_parameter),
                 module: vec![],
                 public: true,
+                deprecation: None,
             },
         )
         .gleam_expect("prelude inserting List type");
@@ -418,6 +432,7 @@ impl<'a, 'b> Env<'a, 'b> {
                 typ: float(),
                 module: vec![],
                 public: true,
+                deprecation: None,
             },
         )
         .gleam_expect("prelude inserting Float type");
@@ -430,6 +445,7 @@ impl<'a, 'b> Env<'a, 'b> {
                 typ: string(),
                 module: vec![],
                 public: true,
+                deprecation: None,
             },
         )
         .gleam_expect("prelude inserting String type");
@@ -444,6 +460,7 @@ impl<'a, 'b> Env<'a, 'b> {
                 typ: result(result_value, result_error),
                 module: vec![],
                 public: true,
+                deprecation: None,
             },
         )
         .gleam_expect("prelude inserting Result type");
@@ -465,6 +482,7 @@ impl<'a, 'b> Env<'a, 'b> {
                 typ: nil(),
                 module: vec![],
                 public: true,
+                deprecation: None,
             },
         )
         .gleam_expect("prelude inserting Nil type");
@@ -543,6 +561,7 @@ impl<'a, 'b> Env<'a, 'b> {
                 origin: Default::default(), // TODO: use the real one
                 variant,
                 typ,
+                deprecation: None,
             },
         );
     }
@@ -594,6 +613,15 @@ impl<'a, 'b> Env<'a, 'b> {
         }
     }
 
+    /// Record that an unqualified imported value has been deprecated so that
+    /// uses of it emit warnings.
+    ///
+    fn insert_variable_deprecation(&mut self, name: &str, deprecation: Option<String>) {
+        if let Some(value) = self.local_values.get_mut(name) {
+            value.deprecation = deprecation;
+        }
+    }
+
     /// Lookup a type in the current scope.
     ///
     pub fn get_type_constructor(
@@ -705,12 +733,19 @@ impl<'a, 'b> Env<'a, 'b> {
                 let TypeConstructor {
                     parameters,
                     typ: return_type,
+                    module: type_module,
+                    deprecation,
                     ..
                 } = self
                     .get_type_constructor(module, name)
                     .map_err(|e| convert_get_type_constructor_error(e, &location))?
                     .clone();
 
+                // Types from this module may be deprecated without warning
+                if type_module != self.current_module {
+                    self.warn_if_deprecated(name, &deprecation, location);
+                }
+
                 // Ensure that the correct number of arguments have been given to the constructor
                 if args.len() != parameters.len() {
                     return Err(Error::IncorrectTypeArity {
@@ -776,6 +811,19 @@ impl<'a, 'b> Env<'a, 'b> {
         }
     }
 
+    /// Emit a warning if a value or type used at this location has been
+    /// deprecated by the module that defines it.
+    ///
+    fn warn_if_deprecated(&mut self, name: &str, deprecation: &Option<String>, location: &SrcSpan) {
+        if let Some(message) = deprecation {
+            self.warnings.push(Warning::DeprecatedItem {
+                location: location.clone(),
+                name: name.to_string(),
+                message: message.clone(),
+            });
+        }
+    }
+
     pub fn insert_accessors(&mut self, type_name: &str, accessors: AccessorsMap) {
         self.accessors.insert(type_name.to_string(), accessors);
     }
@@ -804,6 +852,7 @@ pub struct TypeConstructor {
     pub module: Vec<String>,
     pub parameters: Vec<Arc<Type>>,
     pub typ: Arc<Type>,
+    pub deprecation: Option<String>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -812,6 +861,7 @@ pub struct ValueConstructor {
     pub origin: SrcSpan,
     pub variant: ValueConstructorVariant,
     pub typ: Arc<Type>,
+    pub deprecation: Option<String>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -823,7 +873,7 @@ pub struct TypeAliasConstructor {
 }
 
 impl ValueConstructor {
-    fn field_map(&self) -> Option<&FieldMap> {
+    pub fn field_map(&self) -> Option<&FieldMap> {
         match self.variant {
             ValueConstructorVariant::ModuleFn { ref field_map, .. }
             | ValueConstructorVariant::Record { ref field_map, .. } => field_map.as_ref(),
@@ -1004,11 +1054,24 @@ pub enum Error {
 }
 #[derive(Debug, PartialEq, Clone)]
 pub enum Warning {
-    DeprecatedListPrependSyntax { location: SrcSpan },
+    DeprecatedListPrependSyntax {
+        location: SrcSpan,
+    },
 
-    Todo { location: SrcSpan },
+    Todo {
+        location: SrcSpan,
+        typ: Arc<Type>,
+    },
 
-    ImplicitlyDiscardedResult { location: SrcSpan },
+    DeprecatedItem {
+        location: SrcSpan,
+        name: String,
+        message: String,
+    },
+
+    ImplicitlyDiscardedResult {
+        location: SrcSpan,
+    },
 }
 
 #[derive(Debug, PartialEq)]
@@ -1164,6 +1227,7 @@ fn register_types(
                     public: *public,
                     parameters,
                     typ,
+                    deprecation: statement.deprecation().clone(),
                 },
             )?;
         }
@@ -1187,6 +1251,7 @@ fn register_types(
                     public: *public,
                     parameters,
                     typ,
+                    deprecation: statement.deprecation().clone(),
                 },
             )?;
         }
@@ -1246,6 +1311,7 @@ pub fn infer_module(
                             value.variant.clone(),
                             value.typ.clone(),
                         );
+                        env.insert_variable_deprecation(imported_name, value.deprecation.clone());
                         imported = true;
                     }
 
@@ -1298,102 +1364,364 @@ pub fn infer_module(
         }
     }
 
-    let statements_result: Result<Vec<_>, _> = module
-        .statements
+    // Check that no two values share a name before inferring any, as the
+    // functions are not inferred in the order they are defined
+    if let Err(e) = check_for_duplicate_values(module.statements.as_slice()) {
+        return (Err(e), env.warnings);
+    }
+
+    let statements_result = infer_statements(module.statements, module_name, &mut env);
+
+    let statements = match statements_result {
+        Ok(o) => o,
+        Err(e) => return (Err(e), env.warnings),
+    };
+
+    // Remove private and imported types and values to create the public interface
+    env.module_types
+        .retain(|_, info| info.public && &info.module == module_name);
+    env.module_values.retain(|_, info| info.public);
+    env.accessors.retain(|_, accessors| accessors.public);
+
+    // Ensure no exported values have private types in their type signature
+    for (_, value) in env.module_values.iter() {
+        if let Some(leaked) = value.typ.find_private_type() {
+            return (
+                Err(Error::PrivateTypeLeak {
+                    location: value.origin.clone(),
+                    leaked,
+                }),
+                env.warnings,
+            );
+        }
+    }
+
+    let Env {
+        module_types: types,
+        module_values: values,
+        accessors,
+        warnings,
+        ..
+    } = env;
+
+    (
+        Ok(ast::Module {
+            documentation: module.documentation,
+            name: module.name.clone(),
+            statements,
+            type_info: Module {
+                name: module.name,
+                types,
+                values,
+                accessors,
+            },
+        }),
+        warnings,
+    )
+}
+
+/// Infer the statements of a module. Functions are inferred after all other
+/// statements, in the order given by the module's call graph, so they may
+/// refer to functions defined later in the module.
+///
+fn infer_statements(
+    statements: Vec<UntypedStatement>,
+    module_name: &[String],
+    env: &mut Env,
+) -> Result<Vec<TypedStatement>, Error> {
+    let mut typed: Vec<Option<TypedStatement>> = statements.iter().map(|_| None).collect();
+    let mut functions = vec![];
+
+    for (index, statement) in statements.into_iter().enumerate() {
+        match statement {
+            Statement::Fn { .. } => functions.push((index, statement)),
+            _ => typed[index] = Some(infer_statement(statement, module_name, env)?),
+        }
+    }
+
+    // Infer each group of mutually recursive functions together, with the
+    // groups ordered so that functions are inferred before their callers
+    let groups = call_graph::into_dependency_groups(
+        functions
+            .iter()
+            .map(|(_, statement)| match statement {
+                Statement::Fn {
+                    name, args, body, ..
+                } => (name.as_str(), args.as_slice(), body),
+                _ => crate::error::fatal_compiler_bug("Non-function in call graph"),
+            })
+            .collect::<Vec<_>>()
+            .as_slice(),
+    );
+    let mut functions: Vec<_> = functions.into_iter().map(Some).collect();
+    for group in groups {
+        let (indexes, group): (Vec<_>, Vec<_>) = group
+            .into_iter()
+            .map(|i| {
+                functions[i]
+                    .take()
+                    .gleam_expect("Function inferred more than once")
+            })
+            .unzip();
+        for (index, statement) in
+            indexes
+                .into_iter()
+                .zip(infer_function_group(group, module_name, env)?)
+        {
+            typed[index] = Some(statement);
+        }
+    }
+
+    Ok(typed
         .into_iter()
-        .map(|s| match s {
-            Statement::Fn {
+        .map(|statement| statement.gleam_expect("Statement not inferred"))
+        .collect())
+}
+
+/// Infer a group of functions that may call each other. Each function is
+/// registered with an unbound type so that it can be called before it has
+/// been inferred, and the functions are generalised once all of them have
+/// been inferred.
+///
+fn infer_function_group(
+    functions: Vec<UntypedStatement>,
+    module_name: &[String],
+    env: &mut Env,
+) -> Result<Vec<TypedStatement>, Error> {
+    let level = 1;
+
+    // Register a var for each function so that they can call themselves and
+    // each other recursively
+    let mut recs = Vec::with_capacity(functions.len());
+    for statement in functions.iter() {
+        if let Statement::Fn {
+            name,
+            args,
+            location,
+            ..
+        } = statement
+        {
+            let mut field_map = FieldMap::new(args.len());
+            for (i, arg) in args.iter().enumerate() {
+                if let ArgNames::NamedLabelled { label, .. } = &arg.names {
+                    field_map
+                        .insert(label.clone(), i)
+                        .map_err(|_| Error::DuplicateField {
+                            label: label.to_string(),
+                            location: location.clone(),
+                        })?;
+                }
+            }
+            let field_map = field_map.into_option();
+
+            let rec = env.new_unbound_var(level + 1);
+            env.insert_variable(
+                name.clone(),
+                ValueConstructorVariant::ModuleFn {
+                    name: name.clone(),
+                    field_map: field_map.clone(),
+                    module: module_name.to_vec(),
+                    arity: args.len(),
+                },
+                rec.clone(),
+            );
+            recs.push((field_map, rec));
+        }
+    }
+
+    // Infer the types
+    let mut inferred = Vec::with_capacity(functions.len());
+    for (statement, (field_map, rec)) in functions.into_iter().zip(recs) {
+        if let Statement::Fn {
+            doc,
+            deprecation,
+            location,
+            end_position,
+            name,
+            public,
+            args,
+            body,
+            return_annotation,
+            ..
+        } = statement
+        {
+            let (args, body) = do_infer_fn(args, body, &return_annotation, level + 1, env)?;
+            let args_types = args.iter().map(|a| a.typ.clone()).collect();
+            let typ = fn_(args_types, body.typ());
+
+            // Assert that the inferred type matches the type of any recursive call
+            unify(rec, typ.clone(), env).map_err(|e| convert_unify_error(e, &location))?;
+
+            let statement: TypedStatement = Statement::Fn {
                 doc,
+                deprecation,
                 location,
+                end_position,
                 name,
                 public,
                 args,
-                body,
                 return_annotation,
-                ..
-            } => {
-                let level = 1;
+                return_type: body.typ(),
+                body,
+            };
+            inferred.push((statement, field_map, typ));
+        }
+    }
 
-                let mut field_map = FieldMap::new(args.len());
-                for (i, arg) in args.iter().enumerate() {
-                    if let ArgNames::NamedLabelled { label, .. } = &arg.names {
-                        field_map
-                            .insert(label.clone(), i)
-                            .map_err(|_| Error::DuplicateField {
-                                label: label.to_string(),
-                                location: location.clone(),
-                            })?;
-                    }
-                }
-                let field_map = field_map.into_option();
+    // Generalise the functions now that the whole group has been inferred
+    let mut statements = Vec::with_capacity(inferred.len());
+    for (statement, field_map, typ) in inferred {
+        if let Statement::Fn {
+            name,
+            public,
+            location,
+            args,
+            deprecation,
+            ..
+        } = &statement
+        {
+            let typ = generalise(typ, level);
 
-                // Register a var for the function so that it can call itself recursively
-                let rec = env.new_unbound_var(level + 1);
-                env.insert_variable(
-                    name.clone(),
-                    ValueConstructorVariant::ModuleFn {
+            // Insert the function into the module's interface
+            env.insert_module_value(
+                name,
+                ValueConstructor {
+                    public: *public,
+                    origin: location.clone(),
+                    typ: typ.clone(),
+                    variant: ValueConstructorVariant::ModuleFn {
                         name: name.clone(),
                         field_map: field_map.clone(),
-                        module: module_name.clone(),
+                        module: module_name.to_vec(),
                         arity: args.len(),
                     },
-                    rec.clone(),
-                );
+                    deprecation: deprecation.clone(),
+                },
+            )?;
 
-                // Infer the type
-                let (args, body) =
-                    do_infer_fn(args, body, &return_annotation, level + 1, &mut env)?;
-                let args_types = args.iter().map(|a| a.typ.clone()).collect();
-                let typ = fn_(args_types, body.typ());
+            // Insert the function into the environment
+            env.insert_variable(
+                name.clone(),
+                ValueConstructorVariant::ModuleFn {
+                    name: name.clone(),
+                    field_map,
+                    module: module_name.to_vec(),
+                    arity: args.len(),
+                },
+                typ,
+            );
+        }
+        statements.push(statement);
+    }
 
-                // Assert that the inferred type matches the type of any recursive call
-                unify(rec, typ.clone(), &env).map_err(|e| convert_unify_error(e, &location))?;
-                let typ = generalise(typ, level);
+    Ok(statements)
+}
 
-                // Insert the function into the module's interface
-                env.insert_module_value(
-                    &name,
-                    ValueConstructor {
-                        public,
-                        origin: location.clone(),
-                        typ: typ.clone(),
-                        variant: ValueConstructorVariant::ModuleFn {
-                            name: name.clone(),
-                            field_map: field_map.clone(),
-                            module: module_name.clone(),
-                            arity: args.len(),
-                        },
-                    },
-                )?;
+/// Values are registered as their definitions are inferred, so duplicates
+/// are checked for up front to report them in the order they are defined.
+///
+fn check_for_duplicate_values(statements: &[UntypedStatement]) -> Result<(), Error> {
+    let definitions = statements.iter().flat_map(|statement| match statement {
+        Statement::Fn { name, location, .. } | Statement::ExternalFn { name, location, .. } => {
+            vec![(name, location)]
+        }
+        Statement::CustomType { constructors, .. } => constructors
+            .iter()
+            .map(|constructor| (&constructor.name, &constructor.location))
+            .collect(),
+        Statement::TypeAlias { .. } | Statement::ExternalType { .. } | Statement::Import { .. } => {
+            vec![]
+        }
+    });
 
-                // Insert the function into the environment
-                env.insert_variable(
-                    name.clone(),
-                    ValueConstructorVariant::ModuleFn {
-                        name: name.clone(),
-                        field_map,
-                        module: module_name.clone(),
-                        arity: args.len(),
-                    },
-                    typ,
-                );
+    let mut values: HashMap<&str, &SrcSpan> = HashMap::new();
+    for (name, location) in definitions {
+        if let Some(previous) = values.insert(name, location) {
+            return Err(Error::DuplicateName {
+                location: location.clone(),
+                previous_location: previous.clone(),
+                name: name.to_string(),
+            });
+        }
+    }
+    Ok(())
+}
 
-                let statement: TypedStatement = Statement::Fn {
-                    doc,
-                    location,
-                    name,
-                    public,
-                    args,
-                    return_annotation,
-                    return_type: body.typ(),
-                    body,
-                };
+fn infer_statement(
+    statement: UntypedStatement,
+    module_name: &[String],
+    env: &mut Env,
+) -> Result<TypedStatement, Error> {
+    match statement {
+        Statement::Fn { .. } => {
+            crate::error::fatal_compiler_bug("Function given to infer_statement")
+        }
 
-                Ok(statement)
+        Statement::ExternalFn {
+            doc,
+            deprecation,
+            location,
+            name,
+            public,
+            args,
+            retrn,
+            module,
+            fun,
+            ..
+        } => {
+            // Construct type of function from AST
+            let mut type_vars = hashmap![];
+            let return_type =
+                env.type_from_ast(&retrn, &mut type_vars, NewTypeAction::MakeGeneric)?;
+            let mut args_types = Vec::with_capacity(args.len());
+            let mut field_map = FieldMap::new(args.len());
+            for (i, arg) in args.iter().enumerate() {
+                let t = env.type_from_ast(&arg.typ, &mut type_vars, NewTypeAction::MakeGeneric)?;
+                args_types.push(t);
+                if let Some(label) = &arg.label {
+                    field_map
+                        .insert(label.clone(), i)
+                        .map_err(|_| Error::DuplicateField {
+                            label: label.to_string(),
+                            location: location.clone(),
+                        })?;
+                }
             }
+            let field_map = field_map.into_option();
+            let typ = fn_(args_types, return_type.clone());
 
-            Statement::ExternalFn {
+            // Insert function into module
+            env.insert_module_value(
+                &name,
+                ValueConstructor {
+                    public,
+                    typ: typ.clone(),
+                    origin: location.clone(),
+                    variant: ValueConstructorVariant::ModuleFn {
+                        name: name.clone(),
+                        field_map: field_map.clone(),
+                        module: module_name.to_vec(),
+                        arity: args.len(),
+                    },
+                    deprecation: deprecation.clone(),
+                },
+            )?;
+
+            // Insert function into module's internal scope
+            env.insert_variable(
+                name.clone(),
+                ValueConstructorVariant::ModuleFn {
+                    name: name.clone(),
+                    module: module_name.to_vec(),
+                    arity: args.len(),
+                    field_map,
+                },
+                typ,
+            );
+            Ok(Statement::ExternalFn {
+                return_type,
                 doc,
+                deprecation,
                 location,
                 name,
                 public,
@@ -1401,19 +1729,88 @@ pub fn infer_module(
                 retrn,
                 module,
                 fun,
-                ..
-            } => {
-                // Construct type of function from AST
-                let mut type_vars = hashmap![];
-                let return_type =
-                    env.type_from_ast(&retrn, &mut type_vars, NewTypeAction::MakeGeneric)?;
-                let mut args_types = Vec::with_capacity(args.len());
-                let mut field_map = FieldMap::new(args.len());
-                for (i, arg) in args.iter().enumerate() {
-                    let t =
-                        env.type_from_ast(&arg.typ, &mut type_vars, NewTypeAction::MakeGeneric)?;
+            })
+        }
+
+        Statement::TypeAlias {
+            doc,
+            deprecation,
+            location,
+            public,
+            alias,
+            args,
+            resolved_type,
+            ..
+        } => {
+            let typ = env
+                .get_type_constructor(&None, alias.as_str())
+                .gleam_expect("Could not find existing type for type alias")
+                .typ
+                .clone();
+            Ok(Statement::TypeAlias {
+                doc,
+                deprecation,
+                location,
+                public,
+                alias,
+                args,
+                resolved_type,
+                typ,
+            })
+        }
+
+        Statement::CustomType {
+            doc,
+            deprecation,
+            location,
+            public,
+            name,
+            args,
+            constructors,
+        } => {
+            let mut type_vars = hashmap![];
+
+            // This custom type was inserted into the module types in the `register_types`
+            // pass, so we can expect this type to exist already.
+            let retrn = env
+                .module_types
+                .get(&name)
+                .gleam_expect("Type for custom type not found on constructor infer pass")
+                .typ
+                .clone();
+
+            // Register the parameterised types in the type into type_vars so that they are
+            // used when building the constructors below.
+            for (typ, name) in retrn
+                .app_parameters()
+                .unwrap_or(&[])
+                .iter()
+                .zip(args.iter())
+            {
+                type_vars.insert(name.to_string(), (0, typ.clone()));
+            }
+
+            // If the custom type only has a single constructor then we can access the
+            // fields using the record.field syntax, so store any fields accessors.
+            if let Some(accessors) =
+                custom_type_accessors(constructors.as_slice(), &mut type_vars, env)?
+            {
+                let map = AccessorsMap {
+                    public,
+                    accessors,
+                    typ: retrn.clone(),
+                };
+                env.insert_accessors(name.as_ref(), map)
+            }
+
+            // Check and register constructors
+            for constructor in constructors.iter() {
+                let mut field_map = FieldMap::new(constructor.args.len());
+                let mut args_types = Vec::with_capacity(constructor.args.len());
+                for (i, (label, arg)) in constructor.args.iter().enumerate() {
+                    let t = env.type_from_ast(&arg, &mut type_vars, NewTypeAction::Disallow)?;
                     args_types.push(t);
-                    if let Some(label) = &arg.label {
+                    if let Some(label) = label {
                         field_map
                             .insert(label.clone(), i)
                             .map_err(|_| Error::DuplicateField {
@@ -1422,257 +1819,84 @@ pub fn infer_module(
                             })?;
                     }
                 }
-                let field_map = field_map.into_option();
-                let typ = fn_(args_types, return_type.clone());
-
-                // Insert function into module
-                env.insert_module_value(
-                    &name,
-                    ValueConstructor {
-                        public,
-                        typ: typ.clone(),
-                        origin: location.clone(),
-                        variant: ValueConstructorVariant::ModuleFn {
-                            name: name.clone(),
-                            field_map: field_map.clone(),
-                            module: module_name.clone(),
-                            arity: args.len(),
-                        },
-                    },
-                )?;
-
-                // Insert function into module's internal scope
-                env.insert_variable(
-                    name.clone(),
-                    ValueConstructorVariant::ModuleFn {
-                        name: name.clone(),
-                        module: module_name.clone(),
-                        arity: args.len(),
-                        field_map,
-                    },
-                    typ,
-                );
-                Ok(Statement::ExternalFn {
-                    return_type,
-                    doc,
-                    location,
-                    name,
-                    public,
-                    args,
-                    retrn,
-                    module,
-                    fun,
-                })
-            }
-
-            Statement::TypeAlias {
-                doc,
-                location,
-                public,
-                alias,
-                args,
-                resolved_type,
-                ..
-            } => {
-                let typ = env
-                    .get_type_constructor(&None, alias.as_str())
-                    .gleam_expect("Could not find existing type for type alias")
-                    .typ
-                    .clone();
-                Ok(Statement::TypeAlias {
-                    doc,
-                    location,
-                    public,
-                    alias,
-                    args,
-                    resolved_type,
+                let field_map = field_map.into_option();
+                // Insert constructor function into module scope
+                let typ = match constructor.args.len() {
+                    0 => retrn.clone(),
+                    _ => fn_(args_types, retrn.clone()),
+                };
+                env.insert_module_value(
+                    &constructor.name,
+                    ValueConstructor {
+                        public,
+                        typ: typ.clone(),
+                        origin: constructor.location.clone(),
+                        variant: ValueConstructorVariant::Record {
+                            name: constructor.name.clone(),
+                            arity: args.len(),
+                            field_map: field_map.clone(),
+                        },
+                        deprecation: deprecation.clone(),
+                    },
+                )?;
+                env.insert_variable(
+                    constructor.name.clone(),
+                    ValueConstructorVariant::Record {
+                        name: constructor.name.clone(),
+                        arity: constructor.args.len(),
+                        field_map,
+                    },
                     typ,
-                })
+                );
             }
-
-            Statement::CustomType {
+            Ok(Statement::CustomType {
                 doc,
+                deprecation,
                 location,
                 public,
                 name,
                 args,
                 constructors,
-            } => {
-                let mut type_vars = hashmap![];
-
-                // This custom type was inserted into the module types in the `register_types`
-                // pass, so we can expect this type to exist already.
-                let retrn = env
-                    .module_types
-                    .get(&name)
-                    .gleam_expect("Type for custom type not found on constructor infer pass")
-                    .typ
-                    .clone();
-
-                // Register the parameterised types in the type into type_vars so that they are
-                // used when building the constructors below.
-                for (typ, name) in retrn
-                    .app_parameters()
-                    .unwrap_or(&[])
-                    .iter()
-                    .zip(args.iter())
-                {
-                    type_vars.insert(name.to_string(), (0, typ.clone()));
-                }
-
-                // If the custom type only has a single constructor then we can access the
-                // fields using the record.field syntax, so store any fields accessors.
-                if let Some(accessors) =
-                    custom_type_accessors(constructors.as_slice(), &mut type_vars, &mut env)?
-                {
-                    let map = AccessorsMap {
-                        public,
-                        accessors,
-                        typ: retrn.clone(),
-                    };
-                    env.insert_accessors(name.as_ref(), map)
-                }
+            })
+        }
 
-                // Check and register constructors
-                for constructor in constructors.iter() {
-                    let mut field_map = FieldMap::new(constructor.args.len());
-                    let mut args_types = Vec::with_capacity(constructor.args.len());
-                    for (i, (label, arg)) in constructor.args.iter().enumerate() {
-                        let t = env.type_from_ast(&arg, &mut type_vars, NewTypeAction::Disallow)?;
-                        args_types.push(t);
-                        if let Some(label) = label {
-                            field_map.insert(label.clone(), i).map_err(|_| {
-                                Error::DuplicateField {
-                                    label: label.to_string(),
-                                    location: location.clone(),
-                                }
-                            })?;
-                        }
-                    }
-                    let field_map = field_map.into_option();
-                    // Insert constructor function into module scope
-                    let typ = match constructor.args.len() {
-                        0 => retrn.clone(),
-                        _ => fn_(args_types, retrn.clone()),
-                    };
-                    env.insert_module_value(
-                        &constructor.name,
-                        ValueConstructor {
-                            public,
-                            typ: typ.clone(),
-                            origin: constructor.location.clone(),
-                            variant: ValueConstructorVariant::Record {
-                                name: constructor.name.clone(),
-                                arity: args.len(),
-                                field_map: field_map.clone(),
-                            },
-                        },
-                    )?;
-                    env.insert_variable(
-                        constructor.name.clone(),
-                        ValueConstructorVariant::Record {
-                            name: constructor.name.clone(),
-                            arity: constructor.args.len(),
-                            field_map,
-                        },
-                        typ,
-                    );
-                }
-                Ok(Statement::CustomType {
-                    doc,
-                    location,
-                    public,
-                    name,
-                    args,
-                    constructors,
-                })
+        Statement::ExternalType {
+            doc,
+            location,
+            public,
+            name,
+            args,
+        } => {
+            // Check contained types are valid
+            let mut type_vars = hashmap![];
+            for arg in args.iter() {
+                let var = TypeAst::Var {
+                    location: location.clone(),
+                    name: arg.to_string(),
+                };
+                env.type_from_ast(&var, &mut type_vars, NewTypeAction::MakeGeneric)?;
             }
-
-            Statement::ExternalType {
+            Ok(Statement::ExternalType {
                 doc,
                 location,
                 public,
                 name,
                 args,
-            } => {
-                // Check contained types are valid
-                let mut type_vars = hashmap![];
-                for arg in args.iter() {
-                    let var = TypeAst::Var {
-                        location: location.clone(),
-                        name: arg.to_string(),
-                    };
-                    env.type_from_ast(&var, &mut type_vars, NewTypeAction::MakeGeneric)?;
-                }
-                Ok(Statement::ExternalType {
-                    doc,
-                    location,
-                    public,
-                    name,
-                    args,
-                })
-            }
-
-            Statement::Import {
-                location,
-                module,
-                as_name,
-                unqualified,
-            } => Ok(Statement::Import {
-                location,
-                module,
-                as_name,
-                unqualified,
-            }),
-        })
-        .collect::<Result<Vec<_>, Error>>();
-
-    let statements = match statements_result {
-        Ok(o) => o,
-        Err(e) => return (Err(e), env.warnings),
-    };
-
-    // Remove private and imported types and values to create the public interface
-    env.module_types
-        .retain(|_, info| info.public && &info.module == module_name);
-    env.module_values.retain(|_, info| info.public);
-    env.accessors.retain(|_, accessors| accessors.public);
-
-    // Ensure no exported values have private types in their type signature
-    for (_, value) in env.module_values.iter() {
-        if let Some(leaked) = value.typ.find_private_type() {
-            return (
-                Err(Error::PrivateTypeLeak {
-                    location: value.origin.clone(),
-                    leaked,
-                }),
-                env.warnings,
-            );
+            })
         }
-    }
-
-    let Env {
-        module_types: types,
-        module_values: values,
-        accessors,
-        warnings,
-        ..
-    } = env;
 
-    (
-        Ok(ast::Module {
-            documentation: module.documentation,
-            name: module.name.clone(),
-            statements,
-            type_info: Module {
-                name: module.name,
-                types,
-                values,
-                accessors,
-            },
+        Statement::Import {
+            location,
+            module,
+            as_name,
+            unqualified,
+        } => Ok(Statement::Import {
+            location,
+            module,
+            as_name,
+            unqualified,
         }),
-        warnings,
-    )
+    }
 }
 
 fn custom_type_accessors(
@@ -1709,7 +1933,13 @@ pub fn infer(expr: UntypedExpr, level: usize, env: &mut Env) -> Result<TypedExpr
     match expr {
         UntypedExpr::ListNil { location, .. } => infer_nil(location, level, env),
 
-        UntypedExpr::Todo { location, .. } => infer_todo(location, level, env),
+        UntypedExpr::Todo {
+            location, message, ..
+        } => infer_todo(location, message, level, env),
+
+        UntypedExpr::Panic {
+            location, message, ..
+        } => infer_panic(location, message, level, env),
 
         UntypedExpr::Var { location, name, .. } => infer_var(name, location, level, env),
 
@@ -1758,10 +1988,13 @@ pub fn infer(expr: UntypedExpr, level: usize, env: &mut Env) -> Result<TypedExpr
             location,
             pattern,
             value,
+            annotation,
             then,
             assert,
             ..
-        } => infer_let(pattern, *value, *then, assert, level, location, env),
+        } => infer_let(
+            pattern, *value, annotation, *then, assert, level, location, env,
+        ),
 
         UntypedExpr::Case {
             location,
@@ -1793,6 +2026,21 @@ pub fn infer(expr: UntypedExpr, level: usize, env: &mut Env) -> Result<TypedExpr
             ..
         } => infer_binop(name, *left, *right, level, location, env),
 
+        UntypedExpr::NegateInt { location, value } => Ok(TypedExpr::NegateInt {
+            location,
+            value: Box::new(infer_unary_operand(*value, int(), level, env)?),
+        }),
+
+        UntypedExpr::NegateFloat { location, value } => Ok(TypedExpr::NegateFloat {
+            location,
+            value: Box::new(infer_unary_operand(*value, float(), level, env)?),
+        }),
+
+        UntypedExpr::NotBool { location, value } => Ok(TypedExpr::NotBool {
+            location,
+            value: Box::new(infer_unary_operand(*value, bool(), level, env)?),
+        }),
+
         UntypedExpr::FieldAccess {
             location,
             label,
@@ -1926,13 +2174,48 @@ fn infer_nil(location: SrcSpan, level: usize, env: &mut Env) -> Result<TypedExpr
     })
 }
 
-fn infer_todo(location: SrcSpan, level: usize, env: &mut Env) -> Result<TypedExpr, Error> {
+fn infer_unary_operand(
+    value: UntypedExpr,
+    typ: Arc<Type>,
+    level: usize,
+    env: &mut Env,
+) -> Result<TypedExpr, Error> {
+    let value = infer(value, level, env)?;
+    unify(typ, value.typ(), env).map_err(|e| convert_unify_error(e, value.location()))?;
+    Ok(value)
+}
+
+fn infer_todo(
+    location: SrcSpan,
+    message: Option<String>,
+    level: usize,
+    env: &mut Env,
+) -> Result<TypedExpr, Error> {
+    let typ = env.new_unbound_var(level);
+
+    // The type variable is shared with the warning so that once the
+    // surrounding code has been inferred it holds the type of the hole.
     env.warnings.push(Warning::Todo {
         location: location.clone(),
+        typ: typ.clone(),
     });
 
     Ok(TypedExpr::Todo {
         location,
+        message,
+        typ,
+    })
+}
+
+fn infer_panic(
+    location: SrcSpan,
+    message: Option<String>,
+    level: usize,
+    env: &mut Env,
+) -> Result<TypedExpr, Error> {
+    Ok(TypedExpr::Panic {
+        location,
+        message,
         typ: env.new_unbound_var(level),
     })
 }
@@ -2181,6 +2464,7 @@ fn infer_binop(
         BinOp::DivInt => (int(), int()),
         BinOp::DivFloat => (float(), float()),
         BinOp::ModuloInt => (int(), int()),
+        BinOp::Concatenate => (string(), string()),
     };
 
     let left = infer(left, level, env)?;
@@ -2198,9 +2482,11 @@ fn infer_binop(
     })
 }
 
+#[allow(clippy::too_many_arguments)]
 fn infer_let(
     pattern: UntypedPattern,
     value: UntypedExpr,
+    annotation: Option<TypeAst>,
     then: UntypedExpr,
     assert: bool,
     level: usize,
@@ -2208,6 +2494,15 @@ fn infer_let(
     env: &mut Env,
 ) -> Result<TypedExpr, Error> {
     let value = infer(value, level + 1, env)?;
+
+    // Check that any type annotation is accurate. Type variables in the annotation
+    // are not rigid and may be inferred from the value.
+    if let Some(ann) = &annotation {
+        let ann_typ = env.type_from_ast(ann, &mut hashmap![], NewTypeAction::MakeGeneric)?;
+        let ann_typ = instantiate(ann_typ, level + 1, &mut hashmap![], env);
+        unify(ann_typ, value.typ(), env).map_err(|e| convert_unify_error(e, ann.location()))?;
+    }
+
     let value_typ = generalise(value.typ(), level + 1);
     let pattern = PatternTyper::new(env, level).unify(pattern, value_typ)?;
     let then = infer(then, level, env)?;
@@ -2216,6 +2511,7 @@ fn infer_let(
         location,
         typ,
         pattern,
+        annotation,
         value: Box::new(value),
         then: Box::new(then),
         assert,
@@ -2580,6 +2876,160 @@ fn infer_clause_guard(
         ClauseGuard::Float {
             location, value, ..
         } => Ok(ClauseGuard::Float { location, value }),
+
+        ClauseGuard::String {
+            location, value, ..
+        } => Ok(ClauseGuard::String { location, value }),
+
+        ClauseGuard::Tuple {
+            location, elems, ..
+        } => {
+            let elems = elems
+                .into_iter()
+                .map(|elem| infer_clause_guard(elem, level, env))
+                .collect::<Result<_, _>>()?;
+            Ok(ClauseGuard::Tuple { location, elems })
+        }
+
+        ClauseGuard::TupleIndex {
+            location,
+            index,
+            tuple,
+            ..
+        } => {
+            let tuple = infer_clause_guard(*tuple, level, env)?;
+            match collapse_links(tuple.typ()).as_ref() {
+                Type::Tuple { elems } => {
+                    let typ = elems
+                        .get(index as usize)
+                        .ok_or_else(|| Error::OutOfBoundsTupleIndex {
+                            location: location.clone(),
+                            index,
+                            size: elems.len(),
+                        })?
+                        .clone();
+                    Ok(ClauseGuard::TupleIndex {
+                        location,
+                        index,
+                        typ,
+                        tuple: Box::new(tuple),
+                    })
+                }
+
+                typ if typ.is_unbound() => Err(Error::NotATupleUnbound {
+                    location: tuple.location().clone(),
+                }),
+
+                _ => Err(Error::NotATuple {
+                    location: tuple.location().clone(),
+                    given: tuple.typ(),
+                }),
+            }
+        }
+
+        ClauseGuard::FieldAccess {
+            location,
+            label,
+            container,
+            ..
+        } => {
+            let container = infer_clause_guard(*container, level, env)?;
+            let (index, label, typ) = infer_known_record_access(
+                container.typ(),
+                container.location(),
+                label,
+                &location,
+                env,
+            )?;
+            Ok(ClauseGuard::FieldAccess {
+                location,
+                label,
+                index: Some(index),
+                typ,
+                container: Box::new(container),
+            })
+        }
+
+        ClauseGuard::Constructor {
+            location,
+            module,
+            name,
+            mut args,
+            ..
+        } => {
+            let constructor = env
+                .get_value_constructor(module.as_ref(), &name)
+                .map_err(|e| convert_get_value_constructor_error(e, &location))?
+                .clone();
+            env.warn_if_deprecated(&name, &constructor.deprecation, &location);
+
+            // Only records can be constructed in guards as the BEAM does not permit
+            // calling functions there
+            let (record_name, field_map) = match &constructor.variant {
+                ValueConstructorVariant::Record {
+                    name, field_map, ..
+                } => (name.clone(), field_map.clone()),
+                ValueConstructorVariant::LocalVariable
+                | ValueConstructorVariant::ModuleFn { .. } => {
+                    return Err(Error::NonLocalClauseGuardVariable { location, name })
+                }
+            };
+
+            match &field_map {
+                Some(field_map) if !args.is_empty() => field_map.reorder(&mut args, &location)?,
+                _ => assert_no_labelled_arguments(&args)?,
+            }
+
+            let typ = instantiate(constructor.typ, level, &mut hashmap![], env);
+
+            if args.is_empty() {
+                // A constructor without arguments must be a value rather than a function
+                if let Type::Fn { args, .. } = collapse_links(typ.clone()).as_ref() {
+                    return Err(Error::IncorrectArity {
+                        location,
+                        expected: args.len(),
+                        given: 0,
+                    });
+                }
+                return Ok(ClauseGuard::Constructor {
+                    location,
+                    module,
+                    name: record_name,
+                    args: vec![],
+                    typ,
+                });
+            }
+
+            let (arg_types, typ) = match_fun_type(typ, args.len(), env)
+                .map_err(|e| convert_not_fun_error(e, &location, &location))?;
+            let args = args
+                .into_iter()
+                .zip(arg_types)
+                .map(|(arg, arg_type)| {
+                    let CallArg {
+                        label,
+                        value,
+                        location,
+                    } = arg;
+                    let value = infer_clause_guard(value, level, env)?;
+                    unify(arg_type, value.typ(), env)
+                        .map_err(|e| convert_unify_error(e, value.location()))?;
+                    Ok(CallArg {
+                        label,
+                        value,
+                        location,
+                    })
+                })
+                .collect::<Result<_, _>>()?;
+
+            Ok(ClauseGuard::Constructor {
+                location,
+                module,
+                name: record_name,
+                args,
+                typ,
+            })
+        }
     }
 }
 
@@ -2615,6 +3065,8 @@ fn infer_module_access(
         (module_info.name.clone(), constructor.clone())
     };
 
+    env.warn_if_deprecated(&label, &constructor.deprecation, &select_location);
+
     Ok(TypedExpr::ModuleSelect {
         label,
         typ: instantiate(constructor.typ, level, &mut hashmap![], env),
@@ -2634,24 +3086,45 @@ fn infer_record_access(
 ) -> Result<TypedExpr, Error> {
     // Infer the type of the (presumed) record
     let record = Box::new(infer(record, level, env)?);
+    let (index, label, typ) =
+        infer_known_record_access(record.typ(), record.location(), label, &location, env)?;
+
+    Ok(TypedExpr::RecordAccess {
+        record,
+        label,
+        index,
+        location,
+        typ,
+    })
+}
 
+/// Look up the field with the given label on a value of an already inferred type,
+/// returning the field's position, label, and type for this instance of the record.
+///
+fn infer_known_record_access(
+    record_type: Arc<Type>,
+    record_location: &SrcSpan,
+    label: String,
+    location: &SrcSpan,
+    env: &mut Env,
+) -> Result<(u64, String, Arc<Type>), Error> {
     // If we don't yet know the type of the record then we cannot use any accessors
-    if record.typ().is_unbound() {
+    if record_type.is_unbound() {
         return Err(Error::RecordAccessUnknownType {
-            location: record.location().clone(),
+            location: record_location.clone(),
         });
     }
 
     // Error constructor helper function
     let unknown_field = |fields| Error::UnknownField {
-        typ: record.typ(),
+        typ: record_type.clone(),
         location: location.clone(),
         label: label.clone(),
         fields,
     };
 
     // Check to see if it's a Type that can have accessible fields
-    let accessors = match collapse_links(record.typ().clone()).as_ref() {
+    let accessors = match collapse_links(record_type.clone()).as_ref() {
         // A type in the current module which may have fields
         Type::App { module, name, .. } if module.as_slice() == env.current_module => {
             env.accessors.get(name)
@@ -2683,16 +3156,10 @@ fn infer_record_access(
     let mut type_vars = hashmap![];
     let accessor_record_type = instantiate(accessor_record_type, 0, &mut type_vars, env);
     let typ = instantiate(typ, 0, &mut type_vars, env);
-    unify(accessor_record_type, record.typ().clone(), env)
-        .map_err(|e| convert_unify_error(e, record.location()))?;
+    unify(accessor_record_type, record_type, env)
+        .map_err(|e| convert_unify_error(e, record_location))?;
 
-    Ok(TypedExpr::RecordAccess {
-        record,
-        label,
-        index,
-        location,
-        typ,
-    })
+    Ok((index, label, typ))
 }
 
 struct PatternTyper<'a, 'b, 'c> {
@@ -2953,6 +3420,7 @@ impl<'a, 'b, 'c> PatternTyper<'a, 'b, 'c> {
                 }
 
                 let constructor_typ = cons.typ.clone();
+                let deprecation = cons.deprecation.clone();
                 let constructor = match cons.variant {
                     ValueConstructorVariant::Record { ref name, .. } => {
                         PatternConstructor::Record { name: name.clone() }
@@ -2963,6 +3431,8 @@ impl<'a, 'b, 'c> PatternTyper<'a, 'b, 'c> {
                     ),
                 };
 
+                self.env.warn_if_deprecated(&name, &deprecation, &location);
+
                 let instantiated_constructor_type =
                     instantiate(constructor_typ, self.level, &mut hashmap![], self.env);
                 match &*instantiated_constructor_type {
@@ -3046,6 +3516,7 @@ fn infer_value_constructor(
         variant,
         origin,
         typ,
+        deprecation,
     } = env
         .get_variable(name)
         .cloned()
@@ -3054,12 +3525,14 @@ fn infer_value_constructor(
             name: name.to_string(),
             variables: env.local_values.keys().map(|t| t.to_string()).collect(),
         })?;
+    env.warn_if_deprecated(name, &deprecation, location);
     let typ = instantiate(typ, level, &mut hashmap![], env);
     Ok(ValueConstructor {
         public,
         variant,
         origin,
         typ,
+        deprecation,
     })
 }
 
@@ -0,0 +1,243 @@
+//! The call graph of the functions in a module, used to find the order in
+//! which the functions are to be inferred.
+
+use crate::ast::{ArgNames, ClauseGuard, Pattern, UntypedArg, UntypedExpr};
+use petgraph::{graph::NodeIndex, Graph};
+use std::collections::HashMap;
+
+/// Split the given functions into groups of mutually recursive functions,
+/// returned as indexes into the given slice. The groups are ordered such that
+/// each function is in a group after the groups of the functions it calls.
+///
+pub fn into_dependency_groups(
+    functions: &[(&str, &[UntypedArg], &UntypedExpr)],
+) -> Vec<Vec<usize>> {
+    let mut graph: Graph<usize, ()> = Graph::new();
+    let nodes: Vec<NodeIndex> = (0..functions.len()).map(|i| graph.add_node(i)).collect();
+    let indexes: HashMap<&str, usize> = functions
+        .iter()
+        .enumerate()
+        .map(|(i, (name, _, _))| (*name, i))
+        .collect();
+
+    for (caller, (_, args, body)) in functions.iter().enumerate() {
+        let mut scope = im::HashSet::new();
+        for arg in args.iter() {
+            match &arg.names {
+                ArgNames::Named { name } | ArgNames::NamedLabelled { name, .. } => {
+                    let _ = scope.insert(name.clone());
+                }
+                ArgNames::Discard { .. } | ArgNames::LabelledDiscard { .. } => (),
+            }
+        }
+
+        let mut references = vec![];
+        expr_references(body, &scope, &mut references);
+        for name in references {
+            if let Some(callee) = indexes.get(name) {
+                let _ = graph.update_edge(nodes[caller], nodes[*callee], ());
+            }
+        }
+    }
+
+    // Tarjan's algorithm returns the components in reverse topological order,
+    // so callees come before their callers
+    petgraph::algo::tarjan_scc(&graph)
+        .into_iter()
+        .map(|component| {
+            let mut group: Vec<_> = component.into_iter().map(|node| graph[node]).collect();
+            group.sort();
+            group
+        })
+        .collect()
+}
+
+/// Collect the names of the variables referenced by an expression that are
+/// not bound within it or by the given scope.
+///
+fn expr_references<'a>(
+    expr: &'a UntypedExpr,
+    scope: &im::HashSet<String>,
+    references: &mut Vec<&'a str>,
+) {
+    match expr {
+        UntypedExpr::Int { .. }
+        | UntypedExpr::Float { .. }
+        | UntypedExpr::String { .. }
+        | UntypedExpr::ListNil { .. }
+        | UntypedExpr::Todo { .. }
+        | UntypedExpr::Panic { .. } => (),
+
+        UntypedExpr::Var { name, .. } => {
+            if !scope.contains(name) {
+                references.push(name)
+            }
+        }
+
+        UntypedExpr::Fn { args, body, .. } => {
+            let mut scope = scope.clone();
+            for arg in args.iter() {
+                match &arg.names {
+                    ArgNames::Named { name } | ArgNames::NamedLabelled { name, .. } => {
+                        let _ = scope.insert(name.clone());
+                    }
+                    ArgNames::Discard { .. } | ArgNames::LabelledDiscard { .. } => (),
+                }
+            }
+            expr_references(body, &scope, references)
+        }
+
+        UntypedExpr::Seq { first, then } => {
+            expr_references(first, scope, references);
+            expr_references(then, scope, references);
+        }
+
+        UntypedExpr::ListCons { head, tail, .. } => {
+            expr_references(head, scope, references);
+            expr_references(tail, scope, references);
+        }
+
+        UntypedExpr::Call { fun, args, .. } => {
+            expr_references(fun, scope, references);
+            for arg in args.iter() {
+                expr_references(&arg.value, scope, references);
+            }
+        }
+
+        UntypedExpr::BinOp { left, right, .. } | UntypedExpr::Pipe { left, right, .. } => {
+            expr_references(left, scope, references);
+            expr_references(right, scope, references);
+        }
+
+        UntypedExpr::Let {
+            value,
+            pattern,
+            then,
+            ..
+        } => {
+            expr_references(value, scope, references);
+            let mut scope = scope.clone();
+            pattern_bindings(pattern, &mut scope);
+            expr_references(then, &scope, references);
+        }
+
+        UntypedExpr::Case {
+            subjects, clauses, ..
+        } => {
+            for subject in subjects.iter() {
+                expr_references(subject, scope, references);
+            }
+            for clause in clauses.iter() {
+                let mut scope = scope.clone();
+                for pattern in clause.pattern.iter() {
+                    pattern_bindings(pattern, &mut scope);
+                }
+                if let Some(guard) = &clause.guard {
+                    guard_references(guard, &scope, references);
+                }
+                expr_references(&clause.then, &scope, references);
+            }
+        }
+
+        UntypedExpr::FieldAccess { container, .. } => expr_references(container, scope, references),
+
+        UntypedExpr::Tuple { elems, .. } => {
+            for elem in elems.iter() {
+                expr_references(elem, scope, references);
+            }
+        }
+
+        UntypedExpr::TupleIndex { tuple, .. } => expr_references(tuple, scope, references),
+
+        UntypedExpr::NegateInt { value, .. }
+        | UntypedExpr::NegateFloat { value, .. }
+        | UntypedExpr::NotBool { value, .. } => expr_references(value, scope, references),
+    }
+}
+
+fn guard_references<'a, A>(
+    guard: &'a ClauseGuard<A>,
+    scope: &im::HashSet<String>,
+    references: &mut Vec<&'a str>,
+) {
+    match guard {
+        ClauseGuard::Int { .. } | ClauseGuard::Float { .. } | ClauseGuard::String { .. } => (),
+
+        ClauseGuard::Var { name, .. } => {
+            if !scope.contains(name) {
+                references.push(name)
+            }
+        }
+
+        ClauseGuard::Equals { left, right, .. }
+        | ClauseGuard::NotEquals { left, right, .. }
+        | ClauseGuard::GtInt { left, right, .. }
+        | ClauseGuard::GtEqInt { left, right, .. }
+        | ClauseGuard::LtInt { left, right, .. }
+        | ClauseGuard::LtEqInt { left, right, .. }
+        | ClauseGuard::GtFloat { left, right, .. }
+        | ClauseGuard::GtEqFloat { left, right, .. }
+        | ClauseGuard::LtFloat { left, right, .. }
+        | ClauseGuard::LtEqFloat { left, right, .. }
+        | ClauseGuard::Or { left, right, .. }
+        | ClauseGuard::And { left, right, .. } => {
+            guard_references(left, scope, references);
+            guard_references(right, scope, references);
+        }
+
+        ClauseGuard::Tuple { elems, .. } => {
+            for elem in elems {
+                guard_references(elem, scope, references);
+            }
+        }
+
+        ClauseGuard::TupleIndex { tuple: value, .. }
+        | ClauseGuard::FieldAccess {
+            container: value, ..
+        } => guard_references(value, scope, references),
+
+        ClauseGuard::Constructor { args, .. } => {
+            for arg in args {
+                guard_references(&arg.value, scope, references);
+            }
+        }
+    }
+}
+
+/// Add the variables bound by a pattern to the scope.
+///
+fn pattern_bindings<A>(pattern: &Pattern<A>, scope: &mut im::HashSet<String>) {
+    match pattern {
+        Pattern::Int { .. }
+        | Pattern::Float { .. }
+        | Pattern::String { .. }
+        | Pattern::Discard { .. }
+        | Pattern::Nil { .. } => (),
+
+        Pattern::Var { name, .. } => {
+            let _ = scope.insert(name.clone());
+        }
+
+        Pattern::Let { name, pattern } => {
+            let _ = scope.insert(name.clone());
+            pattern_bindings(pattern, scope);
+        }
+
+        Pattern::Cons { head, tail, .. } => {
+            pattern_bindings(head, scope);
+            pattern_bindings(tail, scope);
+        }
+
+        Pattern::Constructor { args, .. } => {
+            for arg in args.iter() {
+                pattern_bindings(&arg.value, scope);
+            }
+        }
+
+        Pattern::Tuple { elems, .. } => {
+            for elem in elems.iter() {
+                pattern_bindings(elem, scope);
+            }
+        }
+    }
+}
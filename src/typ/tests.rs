@@ -367,6 +367,12 @@ fn infer_test() {
     assert_infer!("1 == todo", "Bool");
     assert_infer!("todo != 1", "Bool");
     assert_infer!("todo + 1", "Int");
+    assert_infer!("todo(\"test\") + 1", "Int");
+
+    // panic
+    assert_infer!("panic", "a");
+    assert_infer!("panic(\"bad\") + 1", "Int");
+    assert_infer!("fn(x) { case x { 1 -> 1 _ -> panic } }", "fn(Int) -> Int");
 
     // tuple index
     assert_infer!("tuple(1, 2.0).0", "Int");
@@ -386,6 +392,46 @@ fn infer_test() {
         "let add = fn(x, _, _) { fn(y) { y + x } } 1 |> add(1, 2, 3)",
         "Int"
     );
+
+    // clause guards
+    assert_infer!("case \"a\" { x if x == \"b\" -> 1 _ -> 2 }", "Int");
+    assert_infer!(
+        "case tuple(1, 2.0) { x if x == tuple(1, 2.0) -> x.1 _ -> 1.0 }",
+        "Float"
+    );
+    assert_infer!("case tuple(1, 2.0) { x if x.0 > 1 -> 1 _ -> 2 }", "Int");
+    assert_infer!("case Ok(1) { x if x == Ok(2) -> 1 _ -> 2 }", "Int");
+    assert_infer!("case [True] { [x] if x == True -> 1 _ -> 2 }", "Int");
+
+    // let annotations
+    assert_infer!("let x: Int = 1 x", "Int");
+    assert_infer!("let x: List(a) = [1] x", "List(Int)");
+    assert_infer!(
+        "let tuple(_, y): tuple(Int, Float) = tuple(1, 2.0) y",
+        "Float"
+    );
+    assert_infer!("assert Ok(x): Result(Int, e) = Ok(1) x", "Int");
+    assert_infer!("let f: fn(a) -> a = fn(x) { x } f", "fn(a) -> a");
+
+    // string concatenation
+    assert_infer!("\"a\" <> \"b\"", "String");
+    assert_infer!("fn(x) { x <> \"!\" }", "fn(String) -> String");
+    assert_infer!("\"a\" <> \"b\" == \"ab\"", "Bool");
+
+    // numeric literals
+    assert_infer!("0xFF + 0o17 + 0b1010 + 1_000_000 + -0x1", "Int");
+    assert_infer!("1.5e-3 +. 1_000.0 +. -2.E10", "Float");
+    assert_infer!("case 1 { 0x1 | 0b10 -> 1.0e1 _ -> 1.0 }", "Float");
+    assert_infer!("tuple(1, 2, 3).0b10", "Int");
+
+    // unary operators
+    assert_infer!("-{1 + 2}", "Int");
+    assert_infer!("1 - -{1}", "Int");
+    assert_infer!("fn(x) { -x }", "fn(Int) -> Int");
+    assert_infer!("fn(x) { -.x }", "fn(Float) -> Float");
+    assert_infer!("fn(x) { !x }", "fn(Bool) -> Bool");
+    assert_infer!("fn(x, y) { x -y }", "fn(Int, Int) -> Int");
+    assert_infer!("!True || !False", "Bool");
 }
 
 #[test]
@@ -401,6 +447,71 @@ fn infer_error_test() {
         };
     }
 
+    assert_error!(
+        "let x: Int = 1.0 x",
+        Error::CouldNotUnify {
+            location: SrcSpan { start: 7, end: 10 },
+            expected: int(),
+            given: float(),
+        },
+    );
+
+    assert_error!(
+        "let x: List(String) = [1] x",
+        Error::CouldNotUnify {
+            location: SrcSpan { start: 7, end: 19 },
+            expected: list(string()),
+            given: list(Arc::new(Type::Var {
+                typ: Arc::new(RefCell::new(TypeVar::Link { typ: int() }))
+            })),
+        },
+    );
+
+    assert_error!(
+        "let x: Wibble = 1 x",
+        Error::UnknownType {
+            location: SrcSpan { start: 7, end: 13 },
+            name: "Wibble".to_string(),
+            types: env_types(),
+        },
+    );
+
+    assert_error!(
+        "\"a\" <> 1",
+        Error::CouldNotUnify {
+            location: SrcSpan { start: 7, end: 8 },
+            expected: string(),
+            given: int(),
+        },
+    );
+
+    assert_error!(
+        "-True",
+        Error::CouldNotUnify {
+            location: SrcSpan { start: 1, end: 5 },
+            expected: int(),
+            given: bool(),
+        },
+    );
+
+    assert_error!(
+        "-.1",
+        Error::CouldNotUnify {
+            location: SrcSpan { start: 2, end: 3 },
+            expected: float(),
+            given: int(),
+        },
+    );
+
+    assert_error!(
+        "!1.0",
+        Error::CouldNotUnify {
+            location: SrcSpan { start: 1, end: 4 },
+            expected: bool(),
+            given: float(),
+        },
+    );
+
     assert_error!(
         "1 + 1.0",
         Error::CouldNotUnify {
@@ -673,6 +784,32 @@ fn infer_error_test() {
         },
     );
 
+    assert_error!(
+        "case tuple(1) { x if x.1 == 1 -> 1 }",
+        Error::OutOfBoundsTupleIndex {
+            location: SrcSpan { start: 22, end: 24 },
+            index: 1,
+            size: 1,
+        },
+    );
+
+    assert_error!(
+        "case 1 { x if x.0 == 1 -> 1 }",
+        Error::NotATuple {
+            location: SrcSpan { start: 14, end: 15 },
+            given: int(),
+        },
+    );
+
+    assert_error!(
+        "case 1 { x if x == \"a\" -> 1 }",
+        Error::CouldNotUnify {
+            location: SrcSpan { start: 14, end: 22 },
+            expected: int(),
+            given: string(),
+        },
+    );
+
     assert_error!(
         "let x = 1.0 case x { _ if x -> 1 }",
         Error::CouldNotUnify {
@@ -1320,6 +1457,59 @@ pub fn get_string(x: Box(String)) { x.inner }
             ("get_string", "fn(Box(String)) -> String"),
         ]
     );
+
+    // Functions can be called before they are defined
+    assert_infer!(
+        "
+pub fn main() { helper(1) }
+pub fn helper(x) { x + 1 }",
+        vec![("helper", "fn(Int) -> Int"), ("main", "fn() -> Int")]
+    );
+
+    // Functions can be mutually recursive
+    assert_infer!(
+        "
+pub fn is_even(x) { case x { 0 -> True n -> is_odd(n - 1) } }
+pub fn is_odd(x) { case x { 0 -> False n -> is_even(n - 1) } }",
+        vec![
+            ("is_even", "fn(Int) -> Bool"),
+            ("is_odd", "fn(Int) -> Bool")
+        ]
+    );
+
+    // Functions called before they are defined are still generalised
+    assert_infer!(
+        "
+pub fn main() { tuple(id(1), id(2.0)) }
+pub fn id(x) { x }",
+        vec![("id", "fn(a) -> a"), ("main", "fn() -> tuple(Int, Float)")]
+    );
+
+    // Records can be constructed and have their fields accessed in guards
+    assert_infer!(
+        "
+pub type Person { Person(name: String, age: Int) }
+pub fn is_bob(p) { case p { _ if p == Person(age: 30, name: \"Bob\") -> True _ -> False } }
+pub fn is_adult(p: Person) { case p { _ if p.age >= 18 -> True _ -> False } }",
+        vec![
+            ("Person", "fn(String, Int) -> Person"),
+            ("is_adult", "fn(Person) -> Bool"),
+            ("is_bob", "fn(Person) -> Bool"),
+        ]
+    );
+
+    // Variables that shadow a function are not calls to it
+    assert_infer!(
+        "
+pub fn main() { let id = 1 id }
+pub fn other(id) { id }
+pub fn id(x) { main() x }",
+        vec![
+            ("id", "fn(a) -> a"),
+            ("main", "fn() -> Int"),
+            ("other", "fn(a) -> a"),
+        ]
+    );
 }
 
 #[test]
@@ -1363,6 +1553,18 @@ fn infer_module_error_test() {
         }
     );
 
+    // Mutually recursive functions are not generalised until the whole group
+    // has been inferred
+    assert_error!(
+        "fn one(x) { two() x }
+fn two() { one(1) one(1.0) }",
+        Error::CouldNotUnify {
+            location: SrcSpan { start: 44, end: 47 },
+            expected: int(),
+            given: float(),
+        }
+    );
+
     assert_error!(
         "
 fn id(x: a, y: a) { x }
@@ -1756,6 +1958,32 @@ fn main() {
         },
     );
 
+    assert_error!(
+        "pub type Person { Person(name: String) }
+fn main(p: Person) { case p { _ if p.height == 1 -> 1 } }",
+        Error::UnknownField {
+            location: SrcSpan { start: 77, end: 84 },
+            label: "height".to_string(),
+            fields: vec!["name".to_string()],
+            typ: Arc::new(Type::App {
+                args: vec![],
+                public: true,
+                module: vec!["my_module".to_string()],
+                name: "Person".to_string(),
+            }),
+        },
+    );
+
+    assert_error!(
+        "pub type Box { Box(Int) }
+fn main(b: Box) { case b { _ if b == Box -> 1 } }",
+        Error::IncorrectArity {
+            location: SrcSpan { start: 63, end: 66 },
+            expected: 1,
+            given: 0,
+        },
+    );
+
     // Cases were we can't so easily check for equality-
     // i.e. because the contents of the error are non-deterministic.
     assert_error!("fn inc(x: a) { x + 1 }");
@@ -1816,10 +2044,27 @@ fn infer_module_warning_test() {
     assert_warning!(
         "fn main() { 1 == todo }",
         Warning::Todo {
-            location: SrcSpan { start: 17, end: 21 }
+            location: SrcSpan { start: 17, end: 21 },
+            typ: Arc::new(Type::Var {
+                typ: Arc::new(RefCell::new(TypeVar::Link { typ: int() })),
+            }),
         },
     );
 
+    // Todos record the type expected of them
+    assert_warning!(
+        "fn main() -> String { todo(\"soon\") }",
+        Warning::Todo {
+            location: SrcSpan { start: 22, end: 34 },
+            typ: Arc::new(Type::Var {
+                typ: Arc::new(RefCell::new(TypeVar::Link { typ: string() })),
+            }),
+        },
+    );
+
+    // Panics do not emit warnings
+    assert_no_warnings!("fn main() { 1 == panic }");
+
     // Implicitly discarded Results emit warnings
     assert_warning!(
         "
@@ -1,4 +1,6 @@
-use crate::diagnostic::{buffer_writer, write, Diagnostic, Severity};
+use crate::diagnostic::{
+    buffer_writer, write, write_project_warning, Diagnostic, ProjectWarningDiagnostic, Severity,
+};
 use std::path::PathBuf;
 use termcolor::Buffer;
 
@@ -11,6 +13,16 @@ pub enum Warning {
         src: Src,
         warning: crate::typ::Warning,
     },
+
+    UnknownConfigKey {
+        path: PathBuf,
+        key: String,
+    },
+
+    InvalidModuleName {
+        path: PathBuf,
+        module: String,
+    },
 }
 
 impl Warning {
@@ -39,7 +51,7 @@ impl Warning {
                         )
                         .unwrap();
                 }
-                Todo { location } => {
+                Todo { location, typ } => {
                     let diagnostic = Diagnostic {
                         title: "Todo found".to_string(),
                         label: "".to_string(),
@@ -49,6 +61,27 @@ impl Warning {
                     };
                     write(buffer, diagnostic, Severity::Warning);
                     writeln!(buffer, "This code will crash if it is run. Be sure to remove this todo before running your program.").unwrap();
+                    writeln!(
+                        buffer,
+                        "\nHint: I think its type is `{}`.",
+                        crate::typ::pretty::Printer::new().pretty_print(typ, 0)
+                    )
+                    .unwrap();
+                }
+                DeprecatedItem {
+                    location,
+                    name,
+                    message,
+                } => {
+                    let diagnostic = Diagnostic {
+                        title: "Deprecated item used".to_string(),
+                        label: "".to_string(),
+                        file: path.to_str().unwrap().to_string(),
+                        src: src.to_string(),
+                        location: location.clone(),
+                    };
+                    write(buffer, diagnostic, Severity::Warning);
+                    writeln!(buffer, "`{}` has been deprecated: {}", name, message).unwrap();
                 }
                 ImplicitlyDiscardedResult { location } => {
                     let diagnostic = Diagnostic {
@@ -62,6 +95,35 @@ impl Warning {
                     writeln!(buffer, "The Result value returned by this code is not being used, so any error is being silently ignored. Check for an error with a case statement, or assign it to the variable _ if you are sure the error does not matter.").unwrap();
                 }
             },
+
+            Warning::UnknownConfigKey { path, key } => {
+                let diagnostic = ProjectWarningDiagnostic {
+                    title: "Unknown config key".to_string(),
+                    label: format!(
+                        "The key `{}` in {} is not used by Gleam and will be ignored.\n",
+                        key,
+                        path.to_string_lossy()
+                    ),
+                };
+                write_project_warning(buffer, diagnostic);
+            }
+
+            Warning::InvalidModuleName { path, module } => {
+                let diagnostic = ProjectWarningDiagnostic {
+                    title: "Invalid module name".to_string(),
+                    label: format!(
+                        "The module `{}` defined in {} does not have a valid name
+and will be ignored.
+
+Each part of a module name must start with a lowercase letter or underscore
+and may only contain lowercase letters, numbers, and underscores.
+",
+                        module,
+                        path.to_string_lossy()
+                    ),
+                };
+                write_project_warning(buffer, diagnostic);
+            }
         }
     }
 